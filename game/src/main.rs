@@ -1,20 +1,88 @@
 use std::{collections::HashSet, time::Instant};
 
 use pixels::Pixels;
-use renderer::{map::Map, player::Player};
+use renderer::{
+    RenderOptions, Renderer,
+    automap::Automap,
+    dither::Dither,
+    framebuffer::{Framebuffer, PixelFormat},
+    hud::Hud,
+    map::Map,
+    player::Player,
+    postfx::PostFx,
+    scale::Scaler,
+};
 use winit::keyboard::KeyCode;
 
+// Not built by `main` until the window loop lands
+#[allow(dead_code)]
 struct Framework {
     device_state: Pixels,
     player: Player,
     map: Map,
     width: u32,
     height: u32,
+    // The game renders at this resolution and `scaler` blows it up to the window
+    frame: Framebuffer,
+    // Reuses its buffers from one frame to the next
+    renderer: Renderer,
+    scaler: Scaler,
+    automap: Automap,
+    show_automap: bool,
+    hud: Hud,
+    // Flashes, shakes and fades on top of the finished frame
+    postfx: PostFx,
+    // Reduces the finished frame to fewer colors, for a retro display look
+    dither: Option<Dither>,
     key_state: HashSet<KeyCode>,
     last_frame_time: Instant
 }
 
+#[allow(dead_code)]
 impl Framework {
+    /// Changes the resolution the game renders at, independent of the window.
+    pub fn set_internal_resolution(&mut self, width: usize, height: usize) {
+        self.frame = Framebuffer::new(width, height, PixelFormat::Rgba8);
+        let viewport = self.hud.viewport(width, height);
+        self.player
+            .set_aspect(viewport.width as f32 / viewport.height.max(1) as f32);
+    }
+
+    pub fn draw(&mut self) {
+        // Effects animate with the time since the previous frame
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_frame_time).as_secs_f32();
+        self.last_frame_time = now;
+        self.postfx.update(dt);
+
+        // The HUD takes the bottom of the screen; the world is drawn above it
+        let viewport = self.hud.viewport(self.frame.width(), self.frame.height());
+        let mut view = self.frame.viewport(viewport);
+        self.renderer.render(
+            &self.player,
+            &self.map,
+            &RenderOptions::default(),
+            &mut view,
+        );
+
+        self.automap.reveal(&self.player, &self.map, viewport.width);
+        if self.show_automap {
+            self.automap.draw(&self.player, &self.map, &mut view);
+        }
+        self.hud.draw(&mut self.frame.view());
+        self.postfx.apply(&mut self.frame.view());
+        if let Some(dither) = &self.dither {
+            dither.apply(&mut self.frame.view());
+        }
+
+        self.scaler.present_rgba8(
+            &self.frame,
+            self.device_state.frame_mut(),
+            self.width as usize,
+            self.height as usize,
+        );
+    }
+
     pub fn log_fps(&self, dt: f32) {
         if dt > 0.0 {
             let fps = 1.0 / dt;
@@ -0,0 +1,64 @@
+//! The `Vector2` operations used per column and per ray.
+//!
+//! ```text
+//! cargo bench -p math --bench vector
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use math::vector::Vector2;
+
+const COUNT: usize = 1024;
+
+/// Fixed inputs, spread around the unit circle at varying lengths.
+fn vectors() -> Vec<Vector2> {
+    (0..COUNT)
+        .map(|i| {
+            let angle = i as f32 * 0.37;
+            let len = 1.0 + (i % 7) as f32;
+            Vector2::new(angle.cos() * len, angle.sin() * len)
+        })
+        .collect()
+}
+
+fn bench_vector(c: &mut Criterion) {
+    let vs = vectors();
+    let plane = Vector2::new(0.66, 0.0);
+
+    let mut group = c.benchmark_group("vector2");
+    group.throughput(Throughput::Elements(COUNT as u64));
+
+    // The camera ray: dir + plane * camera_x
+    group.bench_function("add_scale", |b| {
+        b.iter(|| {
+            vs.iter()
+                .enumerate()
+                .map(|(i, v)| v.add(&plane.scale(i as f32 / COUNT as f32)).x)
+                .sum::<f32>()
+        })
+    });
+    group.bench_function("normalize", |b| {
+        b.iter(|| vs.iter().map(|v| v.normalize().x).sum::<f32>())
+    });
+    group.bench_function("dot", |b| {
+        b.iter(|| vs.iter().map(|v| v.dot(black_box(&plane))).sum::<f32>())
+    });
+    group.bench_function("rotate", |b| {
+        b.iter(|| vs.iter().map(|v| v.rotate(black_box(0.05)).y).sum::<f32>())
+    });
+    group.bench_function("rotate_mut", |b| {
+        b.iter(|| {
+            let mut v = Vector2::new(1.0, 0.0);
+            for _ in 0..COUNT {
+                v.rotate_mut(black_box(0.05));
+            }
+            v
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_vector);
+criterion_main!(benches);
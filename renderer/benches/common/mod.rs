@@ -0,0 +1,79 @@
+//! Scenes shared by the renderer benchmarks. They are fixed so that results
+//! stay comparable across commits.
+//!
+//! Each bench target compiles this module separately and uses only part of it.
+#![allow(dead_code)]
+
+use renderer::{map::Map, player::Player};
+
+/// A walled 32x32 arena with a grid of pillars, seen from a corner.
+pub fn arena() -> (Player, Map) {
+    let mut map = Map::new(32, 32);
+    for i in 0..32 {
+        map.set_tile(i, 0, 1);
+        map.set_tile(i, 31, 1);
+        map.set_tile(0, i, 1);
+        map.set_tile(31, i, 1);
+    }
+    for y in (4..28).step_by(4) {
+        for x in (4..28).step_by(4) {
+            map.set_tile(x, y, 2);
+        }
+    }
+
+    let mut player = Player::new(2.5, 2.5);
+    player.set_angle(math::deg_to_rad(30.0));
+    (player, map)
+}
+
+/// A tiny linear congruential generator, so scenes built from "random"
+/// data are identical on every run without pulling in a RNG crate.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        // Constants from Knuth's MMIX
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 32) as u32
+    }
+
+    /// Returns a float in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+/// A 64x64 map with only its border walled in. Rays travel far.
+pub fn open_map() -> Map {
+    let mut map = Map::new(64, 64);
+    for i in 0..64 {
+        map.set_tile(i, 0, 1);
+        map.set_tile(i, 63, 1);
+        map.set_tile(0, i, 1);
+        map.set_tile(63, i, 1);
+    }
+    map
+}
+
+/// A 64x64 map where roughly a third of the tiles are walls. Rays stop early.
+pub fn dense_map(seed: u64) -> Map {
+    let mut rng = Lcg::new(seed);
+    let mut map = open_map();
+    for y in 1..63 {
+        for x in 1..63 {
+            if rng.next_f32() < 0.3 {
+                map.set_tile(x, y, 1 + (rng.next_u32() % 4) as u8);
+            }
+        }
+    }
+    // Keep the spawn point clear
+    map.set_tile(32, 32, 0);
+    map
+}
@@ -0,0 +1,56 @@
+//! Compares drawing straight into the row-major target with drawing into a
+//! column-major buffer followed by a blocked transpose.
+//!
+//! ```text
+//! cargo bench -p renderer --bench layout
+//! ```
+
+mod common;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use renderer::{
+    RenderLayout, RenderOptions, Renderer,
+    columns::ColumnBuffer,
+    framebuffer::{Framebuffer, PixelFormat},
+};
+
+const RESOLUTIONS: [(usize, usize); 2] = [(640, 400), (1920, 1080)];
+
+fn bench_layouts(c: &mut Criterion) {
+    let (player, map) = common::arena();
+    // Shared like the game's, so no case pays for allocating its buffer
+    let mut renderer = Renderer::new();
+    let mut group = c.benchmark_group("layout");
+    group.sample_size(20);
+
+    for (width, height) in RESOLUTIONS {
+        let mut fb = Framebuffer::new(width, height, PixelFormat::Rgba8);
+        let size = format!("{width}x{height}");
+
+        for (name, layout) in [
+            ("row_major", RenderLayout::RowMajor),
+            ("column_major", RenderLayout::ColumnMajor),
+        ] {
+            let options = RenderOptions {
+                layout,
+                ..RenderOptions::default()
+            };
+            group.bench_with_input(BenchmarkId::new(name, &size), &options, |b, options| {
+                b.iter(|| renderer.render(&player, &map, options, &mut fb.view()))
+            });
+        }
+
+        // The transpose on its own, without any raycasting
+        let columns = ColumnBuffer::new(width, height);
+        group.bench_with_input(
+            BenchmarkId::new("transpose", &size),
+            &columns,
+            |b, columns| b.iter(|| columns.transpose_into(&mut fb.view())),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_layouts);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! Compares the single-threaded renderer with `render_parallel` at 1080p,
+//! using rayon pools of increasing size to show how it scales.
+//!
+//! ```text
+//! cargo bench -p renderer --features parallel --bench parallel
+//! ```
+
+mod common;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use renderer::{
+    RenderOptions, Renderer,
+    framebuffer::{Framebuffer, PixelFormat},
+};
+
+const WIDTH: usize = 1920;
+const HEIGHT: usize = 1080;
+
+fn bench_parallel(c: &mut Criterion) {
+    let (player, map) = common::arena();
+    let options = RenderOptions::default();
+    let mut fb = Framebuffer::new(WIDTH, HEIGHT, PixelFormat::Rgba8);
+    let mut renderer = Renderer::new();
+
+    let mut group = c.benchmark_group("render_1080p");
+    group.sample_size(20);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| renderer.render(&player, &map, &options, &mut fb.view()))
+    });
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut threads = 1;
+    while threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, _| {
+            b.iter(|| {
+                pool.install(|| renderer.render_parallel(&player, &map, &options, &mut fb.view()))
+            })
+        });
+        threads *= 2;
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parallel);
+criterion_main!(benches);
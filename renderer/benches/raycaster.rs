@@ -0,0 +1,56 @@
+//! `Ray::cast` over an open map (long rays) and a dense one (short rays).
+//!
+//! ```text
+//! cargo bench -p renderer --bench raycaster
+//! ```
+
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use math::vector::Vector2;
+use renderer::{map::Map, raycaster::Ray};
+
+const SEED: u64 = 0x0B00_5EED;
+const RAYS: usize = 1024;
+
+/// A fan of ray directions covering the full circle.
+fn directions() -> Vec<Vector2> {
+    (0..RAYS)
+        .map(|i| {
+            let angle = i as f32 / RAYS as f32 * std::f32::consts::TAU;
+            Vector2::new(angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+fn cast_all(map: &Map, origin: Vector2, dirs: &[Vector2]) -> f32 {
+    dirs.iter()
+        .filter_map(|&dir| Ray::new(origin, dir).cast(map))
+        .map(|hit| hit.distance)
+        .sum()
+}
+
+fn bench_cast(c: &mut Criterion) {
+    let dirs = directions();
+    let origin = Vector2::new(32.5, 32.5);
+
+    let mut group = c.benchmark_group("ray_cast");
+    group.throughput(Throughput::Elements(RAYS as u64));
+
+    let open = common::open_map();
+    group.bench_function("open", |b| {
+        b.iter(|| cast_all(black_box(&open), origin, &dirs))
+    });
+
+    let dense = common::dense_map(SEED);
+    group.bench_function("dense", |b| {
+        b.iter(|| cast_all(black_box(&dense), origin, &dirs))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_cast);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Full frames through `render_with` at common resolutions.
+//!
+//! ```text
+//! cargo bench -p renderer --bench render
+//! ```
+
+mod common;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use renderer::{
+    RenderOptions,
+    framebuffer::{Framebuffer, PixelFormat},
+    render_with,
+};
+
+const RESOLUTIONS: [(usize, usize); 4] = [(320, 200), (640, 400), (1280, 720), (1920, 1080)];
+
+fn bench_render(c: &mut Criterion) {
+    let (player, map) = common::arena();
+    let options = RenderOptions::default();
+
+    let mut group = c.benchmark_group("render");
+    group.sample_size(20);
+
+    for (width, height) in RESOLUTIONS {
+        let mut fb = Framebuffer::new(width, height, PixelFormat::Rgba8);
+        group.throughput(Throughput::Elements((width * height) as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{width}x{height}")),
+            |b| b.iter(|| render_with(&player, &map, &options, &mut fb.view())),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+//! `Texture::get_pixel`, `get` and `get_unchecked` with scattered and
+//! column-wise access patterns, in row- and column-major layouts.
+//!
+//! ```text
+//! cargo bench -p renderer --bench texture
+//! ```
+
+mod common;
+
+use std::hint::black_box;
+
+use common::Lcg;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use renderer::texture::{Texture, TextureLayout};
+
+const SEED: u64 = 0x7E57_0000;
+const SIZE: u32 = 256;
+const SAMPLES: usize = 4096;
+
+fn texture() -> Texture {
+    let mut rng = Lcg::new(SEED);
+    Texture::new(
+        SIZE,
+        SIZE,
+        (0..SIZE * SIZE * 4).map(|_| rng.next_u32() as u8).collect(),
+    )
+}
+
+#[allow(deprecated)]
+fn bench_get_pixel(c: &mut Criterion) {
+    let texture = texture();
+    let mut rng = Lcg::new(SEED);
+    let coords: Vec<(u32, u32)> = (0..SAMPLES)
+        .map(|_| (rng.next_u32() % SIZE, rng.next_u32() % SIZE))
+        .collect();
+
+    let mut group = c.benchmark_group("texture_get_pixel");
+    group.throughput(Throughput::Elements(SAMPLES as u64));
+
+    group.bench_function("random", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                .map(|&(x, y)| black_box(&texture).get_pixel(x, y)[0] as u32)
+                .sum::<u32>()
+        })
+    });
+
+    // Walking down a column, the way wall strips are sampled
+    group.bench_function("column", |b| {
+        b.iter(|| {
+            (0..SAMPLES as u32)
+                .map(|i| black_box(&texture).get_pixel(i / SIZE % SIZE, i % SIZE)[0] as u32)
+                .sum::<u32>()
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_get(c: &mut Criterion) {
+    let texture = texture();
+    let column_major = texture.clone().with_layout(TextureLayout::ColumnMajor);
+    let mut rng = Lcg::new(SEED);
+    let coords: Vec<(u32, u32)> = (0..SAMPLES)
+        .map(|_| (rng.next_u32() % SIZE, rng.next_u32() % SIZE))
+        .collect();
+
+    let mut group = c.benchmark_group("texture_get");
+    group.throughput(Throughput::Elements(SAMPLES as u64));
+
+    group.bench_function("random", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                .map(|&(x, y)| black_box(&texture).get(x, y).map_or(0, |c| c.r as u32))
+                .sum::<u32>()
+        })
+    });
+
+    group.bench_function("random_unchecked", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                // SAFETY: coordinates are taken modulo SIZE
+                .map(|&(x, y)| unsafe { black_box(&texture).get_unchecked(x, y) }.r as u32)
+                .sum::<u32>()
+        })
+    });
+
+    // Walking down a column, the way wall strips are sampled
+    for (name, texture) in [("column", &texture), ("column_major", &column_major)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                (0..SAMPLES as u32)
+                    .map(|i| {
+                        black_box(texture)
+                            .get(i / SIZE % SIZE, i % SIZE)
+                            .map_or(0, |c| c.r as u32)
+                    })
+                    .sum::<u32>()
+            })
+        });
+    }
+
+    // Whole strips straight from column-major storage
+    group.bench_function("column_slice", |b| {
+        b.iter(|| {
+            (0..SAMPLES as u32 / SIZE)
+                .flat_map(|x| {
+                    black_box(&column_major)
+                        .column(x % SIZE)
+                        .unwrap()
+                        .chunks_exact(4)
+                })
+                .map(|p| p[0] as u32)
+                .sum::<u32>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_pixel, bench_get);
+criterion_main!(benches);
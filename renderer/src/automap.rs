@@ -0,0 +1,322 @@
+use math::vector::Vector2;
+
+use crate::{
+    color::Color,
+    draw,
+    framebuffer::{FrameView, Rect},
+    map::Map,
+    player::Player,
+    raycaster::Ray,
+};
+
+/// A top-down map of the level, drawn over (or instead of) the 3D view.
+///
+/// The automap remembers which tiles the player has seen. Call `reveal`
+/// every frame, even while the map is hidden, so exploring fills it in.
+pub struct Automap {
+    /// Screen pixels per map tile.
+    pub zoom: f32,
+    /// Turn the map with the player so that forward is always up.
+    pub rotate: bool,
+    /// Cover the whole target instead of a corner of it.
+    pub fullscreen: bool,
+    /// Only draw tiles the player has seen.
+    pub fog_of_war: bool,
+    /// How many slices the drawn view cone is split into. 0 hides it.
+    pub cone_rays: usize,
+
+    pub background: Color,
+    pub wall_color: Color,
+    pub floor_color: Color,
+    pub player_color: Color,
+    pub cone_color: Color,
+
+    width: u32,
+    height: u32,
+    seen: Vec<bool>,
+}
+
+impl Automap {
+    /// The smallest and largest allowed `zoom`.
+    pub const MIN_ZOOM: f32 = 1.0;
+    pub const MAX_ZOOM: f32 = 64.0;
+
+    /// Creates an automap for `map` with nothing seen yet.
+    pub fn new(map: &Map) -> Self {
+        Self {
+            zoom: 8.0,
+            rotate: false,
+            fullscreen: false,
+            fog_of_war: true,
+            cone_rays: 24,
+            background: Color::new(0, 0, 0, 255),
+            wall_color: Color::new(200, 200, 200, 255),
+            floor_color: Color::new(40, 40, 40, 255),
+            player_color: Color::new(255, 255, 0, 255),
+            cone_color: Color::new(0, 160, 0, 255),
+            width: map.width,
+            height: map.height,
+            seen: vec![false; (map.width * map.height) as usize],
+        }
+    }
+
+    /// Multiplies the zoom by `factor`, clamped to `MIN_ZOOM..=MAX_ZOOM`.
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = math::clamp(self.zoom * factor, Self::MIN_ZOOM, Self::MAX_ZOOM);
+    }
+
+    pub fn is_seen(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.seen[(y * self.width + x) as usize]
+    }
+
+    /// Marks every tile as seen, like a map pickup.
+    pub fn reveal_all(&mut self) {
+        self.seen.fill(true);
+    }
+
+    /// Forgets everything seen so far, e.g. when a new level is loaded.
+    pub fn reset(&mut self, map: &Map) {
+        self.width = map.width;
+        self.height = map.height;
+        self.seen.clear();
+        self.seen.resize((map.width * map.height) as usize, false);
+    }
+
+    /// Marks the tiles inside the player's view as seen.
+    ///
+    /// Casts one ray per `width` screen columns, the same way the renderer
+    /// does, and marks the floor along it and the wall it stops at.
+    pub fn reveal(&mut self, player: &Player, map: &Map, width: usize) {
+        if map.width != self.width || map.height != self.height {
+            self.reset(map);
+        }
+
+        self.mark(player.pos.x, player.pos.y);
+        for x in 0..width {
+            let camera_x = 2.0 * (x as f32) / (width as f32) - 1.0;
+            let ray_dir = player.dir.add(&player.plane.scale(camera_x));
+            let Some(res) = Ray::new(player.pos, ray_dir).cast(map) else {
+                continue;
+            };
+
+            // Walk the open floor in quarter tile steps
+            let step = 0.25 / ray_dir.length().max(f32::EPSILON);
+            let mut t = 0.0;
+            while t < res.distance {
+                let p = player.pos.add(&ray_dir.scale(t));
+                self.mark(p.x, p.y);
+                t += step;
+            }
+            if res.map_x >= 0 && res.map_y >= 0 {
+                self.mark(res.map_x as f32, res.map_y as f32);
+            }
+        }
+    }
+
+    fn mark(&mut self, x: f32, y: f32) {
+        if x >= 0.0 && y >= 0.0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.seen[(y as u32 * self.width + x as u32) as usize] = true;
+        }
+    }
+
+    /// Returns the region of a `width` x `height` target the map is drawn into.
+    ///
+    /// Outside full-screen mode this is a square in the top-right corner.
+    pub fn area(&self, width: usize, height: usize) -> Rect {
+        if self.fullscreen {
+            return Rect::new(0, 0, width, height);
+        }
+        let size = width.min(height) / 3;
+        let margin = size / 16;
+        Rect::new(width.saturating_sub(size + margin), margin, size, size)
+    }
+
+    /// Draws the map centered on the player into `area` of `target`.
+    pub fn draw(&self, player: &Player, map: &Map, target: &mut FrameView) {
+        let area = self.area(target.width(), target.height());
+        let mut view = target.sub_view(area);
+        if view.width() == 0 || view.height() == 0 {
+            return;
+        }
+
+        let projection = Projection::new(player, self, &view);
+        let wall = view.format().pack(self.wall_color);
+        let floor = view.format().pack(self.floor_color);
+        let background = view.format().pack(self.background);
+
+        // 1. Tiles: map every pixel back to the tile under it
+        for y in 0..view.height() {
+            let row = view.row_mut(y);
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let p = projection.to_map(x as f32 + 0.5, y as f32 + 0.5);
+                *pixel = if p.x < 0.0 || p.y < 0.0 {
+                    background
+                } else {
+                    let (tx, ty) = (p.x as u32, p.y as u32);
+                    let visible = !self.fog_of_war || self.is_seen(tx, ty);
+                    match map.get_tile(tx, ty) {
+                        Some(tile) if visible && tile > 0 => wall,
+                        Some(_) if visible => floor,
+                        _ => background,
+                    }
+                };
+            }
+        }
+
+        // 2. The view cone, along the rays the renderer actually casts
+        if self.cone_rays > 0 {
+            let (px, py) = projection.to_screen(player.pos);
+            for i in 0..=self.cone_rays {
+                let camera_x = 2.0 * (i as f32) / (self.cone_rays as f32) - 1.0;
+                let ray_dir = player.dir.add(&player.plane.scale(camera_x));
+                let Some(res) = Ray::new(player.pos, ray_dir).cast(map) else {
+                    continue;
+                };
+                let hit = player.pos.add(&ray_dir.scale(res.distance));
+                let (hx, hy) = projection.to_screen(hit);
+                draw::line(&mut view, px, py, hx, hy, self.cone_color);
+            }
+        }
+
+        // 3. The player and where they are facing
+        let (px, py) = projection.to_screen(player.pos);
+        let facing = player.pos.add(&player.dir.normalize().scale(0.6));
+        let (fx, fy) = projection.to_screen(facing);
+        draw::line(&mut view, px, py, fx, fy, self.player_color);
+
+        let radius = (self.zoom / 6.0).max(1.0) as i32;
+        let size = (radius * 2 + 1) as u32;
+        let color = self.player_color;
+        draw::fill_rect(&mut view, px - radius, py - radius, size, size, color);
+    }
+}
+
+/// Converts between map and screen coordinates for one automap frame.
+struct Projection {
+    center: Vector2,
+    pos: Vector2,
+    zoom: f32,
+    /// Map directions of the screen's right and down axes.
+    right: Vector2,
+    down: Vector2,
+}
+
+impl Projection {
+    fn new(player: &Player, automap: &Automap, view: &FrameView) -> Self {
+        let (right, down) = if automap.rotate {
+            let forward = player.dir.normalize();
+            (Vector2::new(-forward.y, forward.x), forward.scale(-1.0))
+        } else {
+            (Vector2::new(1.0, 0.0), Vector2::new(0.0, 1.0))
+        };
+
+        Self {
+            center: Vector2::new(view.width() as f32 / 2.0, view.height() as f32 / 2.0),
+            pos: player.pos,
+            zoom: automap.zoom.max(f32::EPSILON),
+            right,
+            down,
+        }
+    }
+
+    fn to_map(&self, x: f32, y: f32) -> Vector2 {
+        let sx = (x - self.center.x) / self.zoom;
+        let sy = (y - self.center.y) / self.zoom;
+        self.pos
+            .add(&self.right.scale(sx))
+            .add(&self.down.scale(sy))
+    }
+
+    /// Returns the pixel a map position falls on.
+    fn to_screen(&self, p: Vector2) -> (i32, i32) {
+        let d = p.add(&self.pos.scale(-1.0));
+        let x = self.center.x + d.dot(&self.right) * self.zoom;
+        let y = self.center.y + d.dot(&self.down) * self.zoom;
+        (x.floor() as i32, y.floor() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    const ROOM: &str = "\
+#####
+#...#
+#.#.#
+#...#
+#####";
+
+    fn automap(map: &Map) -> Automap {
+        let mut automap = Automap::new(map);
+        automap.fullscreen = true;
+        automap.cone_rays = 0;
+        automap
+    }
+
+    #[test]
+    fn test_reveal_marks_what_the_player_sees() {
+        let map: Map = ROOM.parse().unwrap();
+        let mut player = Player::new(1.5, 3.5);
+        player.set_angle(0.0); // East, along the bottom corridor
+
+        let mut automap = Automap::new(&map);
+        automap.reveal(&player, &map, 32);
+
+        assert!(automap.is_seen(1, 3));
+        assert!(automap.is_seen(3, 3));
+        assert!(automap.is_seen(4, 3)); // The wall at the end
+        assert!(!automap.is_seen(1, 1)); // Behind the player's shoulder
+        assert!(!automap.is_seen(3, 1)); // Hidden by the pillar
+    }
+
+    #[test]
+    fn test_fog_of_war_hides_unseen_tiles() {
+        let map: Map = ROOM.parse().unwrap();
+        let player = Player::new(2.5, 2.5);
+        let mut automap = automap(&map);
+        automap.zoom = 4.0;
+
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        automap.draw(&player, &map, &mut fb.view());
+        // The wall tile (0, 0) lands at the top-left corner
+        assert_eq!(fb.get(1, 1), Some(0xFF000000));
+
+        automap.reveal_all();
+        automap.draw(&player, &map, &mut fb.view());
+        assert_eq!(fb.get(1, 1), Some(0xFFC8C8C8));
+        // Floor tile (1, 1)
+        assert_eq!(fb.get(5, 5), Some(0xFF282828));
+    }
+
+    #[test]
+    fn test_rotate_puts_forward_up() {
+        let map: Map = ROOM.parse().unwrap();
+        let mut player = Player::new(2.5, 3.5);
+        player.set_angle(0.0); // East
+
+        let mut automap = automap(&map);
+        automap.reveal_all();
+        automap.rotate = true;
+        automap.zoom = 4.0;
+
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        automap.draw(&player, &map, &mut fb.view());
+
+        // The wall two tiles east of the player is straight up on screen,
+        // the open floor to the north is to the left
+        assert_eq!(fb.get(10, 1), Some(0xFFC8C8C8));
+        assert_eq!(fb.get(3, 10), Some(0xFF282828));
+    }
+
+    #[test]
+    fn test_corner_overlay_area() {
+        let map = Map::new(4, 4);
+        let mut automap = Automap::new(&map);
+        assert_eq!(automap.area(320, 200), Rect::new(250, 4, 66, 66));
+
+        automap.fullscreen = true;
+        assert_eq!(automap.area(320, 200), Rect::new(0, 0, 320, 200));
+    }
+}
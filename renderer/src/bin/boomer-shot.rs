@@ -0,0 +1,126 @@
+//! Renders a single frame of a text map to a PNG, without opening a window.
+//!
+//! ```text
+//! boomer-shot --map level.txt --pos 1.5,1.5 --angle 90 --size 320x200 -o out.png
+//! ```
+
+use std::{env, fs, process};
+
+use renderer::{
+    RenderOptions, headless,
+    map::Map,
+    player::{Fov, Player},
+};
+
+const USAGE: &str = "\
+Usage: boomer-shot --map <FILE> --pos <X,Y> [--angle <DEGREES>] [--fov <DEGREES>]
+                   [--size <WxH>] -o <FILE>
+
+Options:
+  --map <FILE>        Text map to load (digits/# are walls, . or space is floor)
+  --pos <X,Y>         Camera position in map units
+  --angle <DEGREES>   View direction, 0 = +X (east), 90 = +Y (south) [default: 0]
+  --fov <DEGREES>     Horizontal field of view [default: 66]
+  --size <WxH>        Output resolution [default: 320x200]
+  -o, --output <FILE> Where to write the PNG";
+
+struct Args {
+    map: String,
+    pos: (f32, f32),
+    angle: f32,
+    fov: f32,
+    size: (u32, u32),
+    output: String,
+}
+
+fn parse_pair<T: std::str::FromStr>(value: &str, sep: char) -> Option<(T, T)> {
+    let (a, b) = value.split_once(sep)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut map = None;
+    let mut pos = None;
+    let mut angle = 0.0;
+    let mut fov = 66.0;
+    let mut size = (320, 200);
+    let mut output = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            println!("{USAGE}");
+            process::exit(0);
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--map" => map = Some(value),
+            "--pos" => {
+                pos = Some(parse_pair(&value, ',').ok_or(format!("invalid --pos: {value}"))?)
+            }
+            "--angle" => {
+                angle = value
+                    .parse()
+                    .map_err(|_| format!("invalid --angle: {value}"))?
+            }
+            "--fov" => {
+                fov = value
+                    .parse()
+                    .ok()
+                    .filter(|fov| *fov > 0.0 && *fov < 180.0)
+                    .ok_or(format!("invalid --fov: {value}"))?
+            }
+            "--size" => {
+                size = parse_pair(&value, 'x').ok_or(format!("invalid --size: {value}"))?;
+                if size.0 == 0 || size.1 == 0 {
+                    return Err(format!("invalid --size: {value}"));
+                }
+            }
+            "-o" | "--output" => output = Some(value),
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+
+    Ok(Args {
+        map: map.ok_or("--map is required")?,
+        pos: pos.ok_or("--pos is required")?,
+        angle,
+        fov,
+        size,
+        output: output.ok_or("-o is required")?,
+    })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let text = fs::read_to_string(&args.map).map_err(|e| format!("{}: {e}", args.map))?;
+    let map: Map = text.parse().map_err(|e| format!("{}: {e}", args.map))?;
+
+    let mut player = Player::new(args.pos.0, args.pos.1);
+    player.set_angle(math::deg_to_rad(args.angle));
+
+    let (width, height) = args.size;
+    player.set_fov(Fov::Horizontal(args.fov), width as f32 / height as f32);
+    headless::render_to_png(
+        &player,
+        &map,
+        &RenderOptions::default(),
+        width,
+        height,
+        &args.output,
+    )
+    .map_err(|e| format!("{}: {e}", args.output))
+}
+
+fn main() {
+    let result = parse_args()
+        .map_err(|e| format!("{e}\n\n{USAGE}"))
+        .and_then(run);
+
+    if let Err(message) = result {
+        eprintln!("error: {message}");
+        process::exit(1);
+    }
+}
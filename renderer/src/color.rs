@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, Debug)]
+use crate::framebuffer::PixelFormat;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 /// A simple RGBA color structure for the raycaster.
 pub struct Color {
     /// Red channel (0-255)
@@ -11,7 +13,25 @@ pub struct Color {
     pub a: u8,
 }
 
+/// How `Color::blend` combines a color with the one below it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Paint over, by alpha.
+    #[default]
+    Normal,
+    /// Brighten: channels are summed and saturate at 255.
+    Add,
+    /// Darken: channels are multiplied, so white leaves the color below unchanged.
+    Multiply,
+    /// The inverse of multiply: brightens, and black leaves the color below unchanged.
+    Screen,
+}
+
 impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0, 255);
+    pub const WHITE: Color = Color::new(255, 255, 255, 255);
+    pub const TRANSPARENT: Color = Color::new(0, 0, 0, 0);
+
     /// Creates a new color from raw RGBA components.
     pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
@@ -38,6 +58,41 @@ impl Color {
         }
     }
 
+    /// Creates a color from a `0xAARRGGBB` integer.
+    pub const fn from_argb(argb: u32) -> Self {
+        Self {
+            r: (argb >> 16) as u8,
+            g: (argb >> 8) as u8,
+            b: argb as u8,
+            a: (argb >> 24) as u8,
+        }
+    }
+
+    /// Returns the color as a `0xAARRGGBB` integer, as `PixelFormat::Argb32` stores it.
+    pub const fn to_argb(&self) -> u32 {
+        (self.a as u32) << 24 | (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+
+    /// Creates a color from a `0xRRGGBBAA` integer. Same as `from_hex_alpha`.
+    pub const fn from_rgba(rgba: u32) -> Self {
+        Self::from_hex_alpha(rgba)
+    }
+
+    /// Returns the color as a `0xRRGGBBAA` integer.
+    pub const fn to_rgba(&self) -> u32 {
+        (self.r as u32) << 24 | (self.g as u32) << 16 | (self.b as u32) << 8 | self.a as u32
+    }
+
+    /// Returns the color packed exactly as `render` writes it into a buffer of `format`.
+    pub fn to_u32(&self, format: PixelFormat) -> u32 {
+        format.pack(*self)
+    }
+
+    /// Unpacks a pixel read from a buffer of `format`.
+    pub fn from_u32(pixel: u32, format: PixelFormat) -> Self {
+        format.unpack(pixel)
+    }
+
     /// Returns the color as a `[u8; 4]` array in RGBA order.
     /// This is compatible with the `pixels` crate buffer format.
     pub fn to_array(&self) -> [u8; 4] {
@@ -47,15 +102,238 @@ impl Color {
     /// Returns a new color with the RGB channels multiplied by `factor`.
     ///
     /// Useful for simple shading. A factor of 0.5 makes the color half as bright.
+    /// Factors above 1.0 brighten, saturating at 255.
     /// Note: The Alpha channel remains unchanged.
     pub fn darkened(&self, factor: f32) -> Self {
+        let scale = |c: u8| math::clamp((c as f32 * factor).round(), 0.0, 255.0) as u8;
         Self {
-            r: (self.r as f32 * factor).round() as u8,
-            g: (self.g as f32 * factor).round() as u8,
-            b: (self.b as f32 * factor).round() as u8,
+            r: scale(self.r),
+            g: scale(self.g),
+            b: scale(self.b),
             a: self.a, // Keep walls opaque!
         }
     }
+
+    /// Sums the RGB channels, saturating at 255. Keeps `self`'s alpha.
+    pub fn add(&self, other: &Color) -> Self {
+        Self {
+            r: self.r.saturating_add(other.r),
+            g: self.g.saturating_add(other.g),
+            b: self.b.saturating_add(other.b),
+            a: self.a,
+        }
+    }
+
+    /// Multiplies the RGB channels (255 = unchanged). Keeps `self`'s alpha.
+    ///
+    /// This is how surfaces are lit: `surface.multiply(&light)`.
+    pub fn multiply(&self, other: &Color) -> Self {
+        let mul = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+        Self {
+            r: mul(self.r, other.r),
+            g: mul(self.g, other.g),
+            b: mul(self.b, other.b),
+            a: self.a,
+        }
+    }
+
+    /// Screens the RGB channels: `1 - (1 - a) * (1 - b)`. Keeps `self`'s alpha.
+    pub fn screen(&self, other: &Color) -> Self {
+        let screen = |a: u8, b: u8| 255 - (((255 - a) as u32 * (255 - b) as u32 + 127) / 255) as u8;
+        Self {
+            r: screen(self.r, other.r),
+            g: screen(self.g, other.g),
+            b: screen(self.b, other.b),
+            a: self.a,
+        }
+    }
+
+    /// Composites this color over `below` using straight (non-premultiplied) alpha.
+    pub fn over(&self, below: &Color) -> Self {
+        let src_a = self.a as f32 / 255.0;
+        let dst_a = below.a as f32 / 255.0 * (1.0 - src_a);
+        let out_a = src_a + dst_a;
+        if out_a <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let mix = |s: u8, d: u8| ((s as f32 * src_a + d as f32 * dst_a) / out_a).round() as u8;
+        Self {
+            r: mix(self.r, below.r),
+            g: mix(self.g, below.g),
+            b: mix(self.b, below.b),
+            a: (out_a * 255.0).round() as u8,
+        }
+    }
+
+    /// Returns the color with its RGB channels multiplied by its alpha.
+    pub fn premultiplied(&self) -> Self {
+        self.multiply(&Color::new(self.a, self.a, self.a, self.a))
+    }
+
+    /// Undoes `premultiplied`. Fully transparent colors become transparent black.
+    pub fn unpremultiplied(&self) -> Self {
+        if self.a == 0 {
+            return Self::TRANSPARENT;
+        }
+        let div = |c: u8| ((c as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8;
+        Self {
+            r: div(self.r),
+            g: div(self.g),
+            b: div(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Composites this color over `below` when both are premultiplied.
+    pub fn over_premultiplied(&self, below: &Color) -> Self {
+        let keep = 255 - self.a;
+        let mix = |s: u8, d: u8| s.saturating_add(((d as u32 * keep as u32 + 127) / 255) as u8);
+        Self {
+            r: mix(self.r, below.r),
+            g: mix(self.g, below.g),
+            b: mix(self.b, below.b),
+            a: mix(self.a, below.a),
+        }
+    }
+
+    /// Combines this color with `below` using `mode`, then composites the
+    /// result over `below` by this color's alpha.
+    pub fn blend(&self, below: &Color, mode: BlendMode) -> Self {
+        let mixed = match mode {
+            BlendMode::Normal => *self,
+            BlendMode::Add => below.add(self),
+            BlendMode::Multiply => below.multiply(self),
+            BlendMode::Screen => below.screen(self),
+        };
+        Color { a: self.a, ..mixed }.over(below)
+    }
+
+    /// Returns hue (degrees, 0 to 360), saturation and value (0.0 to 1.0).
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Creates an opaque color from hue (degrees), saturation and value (0.0 to 1.0).
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let s = math::clamp(saturation, 0.0, 1.0);
+        let v = math::clamp(value, 0.0, 1.0);
+        let chroma = v * s;
+        Self::from_hue(hue, chroma, v - chroma)
+    }
+
+    /// Returns hue (degrees, 0 to 360), saturation and lightness (0.0 to 1.0).
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Creates an opaque color from hue (degrees), saturation and lightness (0.0 to 1.0).
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let s = math::clamp(saturation, 0.0, 1.0);
+        let l = math::clamp(lightness, 0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_hue(hue, chroma, l - chroma / 2.0)
+    }
+
+    /// The hue in degrees plus the largest and smallest channel, all channels as 0.0 to 1.0.
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// Builds a color from a hue, its chroma and the amount added to every channel.
+    fn from_hue(hue: f32, chroma: f32, base: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| math::clamp(((c + base) * 255.0).round(), 0.0, 255.0) as u8;
+        Self::new(channel(r), channel(g), channel(b), 255)
+    }
+
+    /// Returns the channels as linear light (0.0 to 1.0), decoding the sRGB curve.
+    /// Alpha is already linear and is only rescaled.
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a as f32 / 255.0,
+        ]
+    }
+
+    /// Encodes linear light channels (0.0 to 1.0) back to sRGB.
+    pub fn from_linear(rgba: [f32; 4]) -> Self {
+        Self::new(
+            linear_to_srgb(rgba[0]),
+            linear_to_srgb(rgba[1]),
+            linear_to_srgb(rgba[2]),
+            math::clamp((rgba[3] * 255.0).round(), 0.0, 255.0) as u8,
+        )
+    }
+
+    /// Linearly interpolates between this color and `other`.
+    ///
+    /// `t = 0.0` returns `self`, `t = 1.0` returns `other`. All four channels are blended.
+    pub fn lerp(&self, other: &Color, t: f32) -> Self {
+        let t = math::clamp(t, 0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+}
+
+/// Decodes an 8-bit sRGB channel to linear light.
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes linear light (0.0 to 1.0) as an 8-bit sRGB channel.
+pub fn linear_to_srgb(v: f32) -> u8 {
+    let v = math::clamp(v, 0.0, 1.0);
+    let c = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
 }
 
 #[cfg(test)]
@@ -88,6 +366,107 @@ mod tests {
         assert_eq!(gray.a, 255); // Alpha should stay the same
     }
 
+    #[test]
+    fn test_lerp() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+        assert_eq!(black.lerp(&white, 0.0).to_array(), [0, 0, 0, 255]);
+        assert_eq!(black.lerp(&white, 0.5).to_array(), [128, 128, 128, 255]);
+        assert_eq!(black.lerp(&white, 2.0).to_array(), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_darkened_clamps_when_brightening() {
+        let color = Color::new(100, 200, 0, 255);
+        assert_eq!(color.darkened(2.0), Color::new(200, 255, 0, 255));
+        assert_eq!(color.darkened(-1.0), Color::new(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn test_packed_orders() {
+        let color = Color::new(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(color.to_argb(), 0x44112233);
+        assert_eq!(color.to_rgba(), 0x11223344);
+        assert_eq!(Color::from_argb(0x44112233), color);
+        assert_eq!(Color::from_rgba(0x11223344), color);
+
+        for format in [PixelFormat::Rgba8, PixelFormat::Bgra8, PixelFormat::Argb32] {
+            assert_eq!(Color::from_u32(color.to_u32(format), format), color);
+        }
+        assert_eq!(color.to_u32(PixelFormat::Argb32), color.to_argb());
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let gray = Color::new(128, 128, 128, 255);
+        assert_eq!(gray.add(&gray), Color::new(255, 255, 255, 255));
+        assert_eq!(Color::WHITE.multiply(&gray), gray);
+        assert_eq!(gray.multiply(&Color::WHITE), gray);
+        assert_eq!(Color::BLACK.screen(&gray), gray);
+        assert_eq!(gray.screen(&gray), Color::new(192, 192, 192, 255));
+
+        // A half transparent additive color adds half of itself
+        let glow = Color::new(100, 0, 0, 128);
+        assert_eq!(glow.blend(&gray, BlendMode::Add).r, 178);
+        assert_eq!(glow.blend(&gray, BlendMode::Normal).r, 114);
+    }
+
+    #[test]
+    fn test_alpha_compositing() {
+        let red = Color::new(255, 0, 0, 128);
+        assert_eq!(red.over(&Color::BLACK), Color::new(128, 0, 0, 255));
+        assert_eq!(
+            Color::TRANSPARENT.over(&Color::TRANSPARENT),
+            Color::TRANSPARENT
+        );
+
+        // Half over half leaves three quarters coverage, still pure red
+        let both = red.over(&red);
+        assert_eq!((both.r, both.a), (255, 192));
+
+        // The premultiplied path gives the same result
+        let pre = red.premultiplied();
+        assert_eq!(pre, Color::new(128, 0, 0, 128));
+        assert_eq!(
+            pre.over_premultiplied(&Color::BLACK),
+            Color::new(128, 0, 0, 255)
+        );
+        assert_eq!(pre.unpremultiplied(), Color::new(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn test_hsv_hsl_round_trip() {
+        let orange = Color::new(255, 128, 0, 255);
+        let (h, s, v) = orange.to_hsv();
+        assert!((h - 30.1).abs() < 0.1);
+        assert_eq!((s, v), (1.0, 1.0));
+        assert_eq!(Color::from_hsv(h, s, v), orange);
+
+        let (h, s, l) = orange.to_hsl();
+        assert!((l - 0.5).abs() < 0.01);
+        assert_eq!(Color::from_hsl(h, s, l), orange);
+
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::new(0, 0, 255, 255));
+        assert_eq!(
+            Color::from_hsl(0.0, 0.0, 0.5),
+            Color::new(128, 128, 128, 255)
+        );
+    }
+
+    #[test]
+    fn test_srgb_linear() {
+        assert_eq!(srgb_to_linear(0), 0.0);
+        assert_eq!(srgb_to_linear(255), 1.0);
+        // Mid gray in sRGB is only about a fifth of the light
+        assert!((srgb_to_linear(128) - 0.2159).abs() < 1e-3);
+        for c in 0..=255 {
+            assert_eq!(linear_to_srgb(srgb_to_linear(c)), c);
+        }
+
+        let color = Color::new(10, 128, 250, 64);
+        assert_eq!(Color::from_linear(color.to_linear()), color);
+    }
+
     #[test]
     fn test_to_array() {
         let color = Color::new(1, 2, 3, 4);
@@ -0,0 +1,112 @@
+use crate::framebuffer::FrameView;
+
+/// Side of the square tiles the transpose works on. 16x16 `u32`s is 1 KiB,
+/// so a source and a destination tile fit comfortably in L1.
+const BLOCK: usize = 16;
+
+/// A column-major render target.
+///
+/// The raycaster draws one vertical strip at a time. Writing those strips
+/// into a row-major buffer strides a whole row per pixel, which thrashes the
+/// cache. Here each column is contiguous, and the finished frame is copied
+/// to the row-major output in one cache-friendly `transpose_into` pass.
+pub struct ColumnBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl ColumnBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Resizes the buffer, keeping the allocation when it is big enough.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.pixels.resize(width * height, 0);
+    }
+
+    /// Returns column `x`, `height` pixels long from top to bottom.
+    pub fn column_mut(&mut self, x: usize) -> &mut [u32] {
+        &mut self.pixels[x * self.height..(x + 1) * self.height]
+    }
+
+    /// The raw pixels, one column after another.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    /// Copies the buffer into a row-major view, one `BLOCK`-sized tile at a time.
+    ///
+    /// Only the overlapping area is copied if the sizes differ.
+    pub fn transpose_into(&self, target: &mut FrameView) {
+        let width = self.width.min(target.width());
+        let height = self.height.min(target.height());
+
+        for by in (0..height).step_by(BLOCK) {
+            let y_end = (by + BLOCK).min(height);
+            for bx in (0..width).step_by(BLOCK) {
+                let x_end = (bx + BLOCK).min(width);
+                for y in by..y_end {
+                    let row = &mut target.row_mut(y)[bx..x_end];
+                    for (x, pixel) in (bx..x_end).zip(row) {
+                        *pixel = self.pixels[x * self.height + y];
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    #[test]
+    fn test_transpose_matches_naive_copy() {
+        // Sizes that are not multiples of the block size
+        let (width, height) = (37, 21);
+        let mut columns = ColumnBuffer::new(width, height);
+        for x in 0..width {
+            for (y, pixel) in columns.column_mut(x).iter_mut().enumerate() {
+                *pixel = (x * 1000 + y) as u32;
+            }
+        }
+
+        let mut fb = Framebuffer::with_stride(width, height, 40, PixelFormat::Argb32);
+        columns.transpose_into(&mut fb.view());
+
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(fb.get(x, y), Some((x * 1000 + y) as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn test_resize_keeps_columns_contiguous() {
+        let mut columns = ColumnBuffer::new(2, 2);
+        columns.resize(3, 4);
+        assert_eq!(columns.pixels().len(), 12);
+        assert_eq!(columns.column_mut(2).len(), 4);
+    }
+}
@@ -0,0 +1,448 @@
+//! Dithering post-processes that reduce a finished frame to fewer colors.
+//!
+//! Quantizing a smooth gradient (fog, light falloff) straight to a small set
+//! of colors leaves visible bands. Dithering trades the bands for a fine
+//! pattern of the neighbouring colors, which reads as the original shade
+//! from a distance and gives the frame the look of an old display.
+
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use crate::{color::Color, framebuffer::FrameView, palette::Palette};
+
+/// Side of the tiling blue-noise threshold map.
+const BLUE_NOISE_SIZE: usize = 32;
+
+/// How far ordered dithering pushes a color when quantizing to a palette,
+/// where there is no single step between neighbouring colors.
+const PALETTE_SPREAD: f32 = 32.0;
+
+/// How the error between a color and its quantized value is spread out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DitherMethod {
+    /// Plain quantization, with banding.
+    #[default]
+    None,
+    /// Ordered dithering with a 2x2, 4x4 or 8x8 Bayer matrix: a regular
+    /// crosshatch, cheap and stable between frames.
+    Bayer2,
+    Bayer4,
+    Bayer8,
+    /// Ordered dithering with a blue-noise threshold map: no visible
+    /// pattern, just fine grain.
+    BlueNoise,
+    /// Floyd–Steinberg error diffusion: the closest match to the original,
+    /// but the pattern shifts whenever anything on screen changes.
+    FloydSteinberg,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDitherMethodError(String);
+
+impl fmt::Display for ParseDitherMethodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown dither method `{}` (expected none, bayer2, bayer4, bayer8, \
+             blue-noise or floyd-steinberg)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseDitherMethodError {}
+
+impl FromStr for DitherMethod {
+    type Err = ParseDitherMethodError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(DitherMethod::None),
+            "bayer2" => Ok(DitherMethod::Bayer2),
+            "bayer4" => Ok(DitherMethod::Bayer4),
+            "bayer8" => Ok(DitherMethod::Bayer8),
+            "blue-noise" => Ok(DitherMethod::BlueNoise),
+            "floyd-steinberg" => Ok(DitherMethod::FloydSteinberg),
+            _ => Err(ParseDitherMethodError(s.to_string())),
+        }
+    }
+}
+
+/// The colors a frame is reduced to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Quantize {
+    /// The nearest entry of a palette, through `Palette::lookup`.
+    Palette(Box<Palette>),
+    /// Evenly spaced levels with this many bits per channel, e.g. 5-6-5
+    /// for a 16-bit display. 8 bits leaves a channel untouched.
+    Bits { red: u32, green: u32, blue: u32 },
+}
+
+impl Quantize {
+    /// 16-bit high color.
+    pub const RGB565: Quantize = Quantize::Bits {
+        red: 5,
+        green: 6,
+        blue: 5,
+    };
+
+    /// Returns the closest color this target can show. Alpha is kept.
+    pub fn apply(&self, color: Color) -> Color {
+        match self {
+            Quantize::Palette(palette) => {
+                let mut quantized = palette.get(palette.lookup(color));
+                quantized.a = color.a;
+                quantized
+            }
+            &Quantize::Bits { red, green, blue } => Color::new(
+                quantize_channel(color.r as f32, red),
+                quantize_channel(color.g as f32, green),
+                quantize_channel(color.b as f32, blue),
+                color.a,
+            ),
+        }
+    }
+
+    /// Returns the distance between neighbouring levels of each channel,
+    /// which is how far ordered dithering needs to push a color.
+    fn spread(&self) -> [f32; 3] {
+        match *self {
+            Quantize::Palette(_) => [PALETTE_SPREAD; 3],
+            Quantize::Bits { red, green, blue } => [step(red), step(green), step(blue)],
+        }
+    }
+}
+
+/// Returns the distance between levels of a channel with `bits` bits.
+fn step(bits: u32) -> f32 {
+    let bits = bits.clamp(1, 8);
+    255.0 / ((1 << bits) - 1) as f32
+}
+
+fn quantize_channel(value: f32, bits: u32) -> u8 {
+    let step = step(bits);
+    let value = (value / step).round() * step;
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// A dithering post-process. Change the fields at any time; the next
+/// `apply` uses them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dither {
+    pub method: DitherMethod,
+    pub quantize: Quantize,
+    /// Scales the ordered dithering offsets and the diffused error.
+    /// 1.0 is the textbook amount, 0.0 plain quantization.
+    pub strength: f32,
+}
+
+impl Dither {
+    pub fn new(method: DitherMethod, quantize: Quantize) -> Self {
+        Self {
+            method,
+            quantize,
+            strength: 1.0,
+        }
+    }
+
+    /// Quantizes every pixel of `target` in place.
+    pub fn apply(&self, target: &mut FrameView) {
+        match self.method {
+            DitherMethod::None => self.ordered(target, |_, _| 0.5),
+            DitherMethod::Bayer2 => self.ordered(target, |x, y| bayer(x, y, 1)),
+            DitherMethod::Bayer4 => self.ordered(target, |x, y| bayer(x, y, 2)),
+            DitherMethod::Bayer8 => self.ordered(target, |x, y| bayer(x, y, 3)),
+            DitherMethod::BlueNoise => {
+                let noise = blue_noise();
+                self.ordered(target, |x, y| {
+                    noise[(y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE]
+                })
+            }
+            DitherMethod::FloydSteinberg => self.floyd_steinberg(target),
+        }
+    }
+
+    /// Offsets each pixel by its threshold (0.0 to 1.0) before quantizing.
+    fn ordered(&self, target: &mut FrameView, threshold: impl Fn(usize, usize) -> f32) {
+        let format = target.format();
+        let spread = self.quantize.spread();
+
+        for y in 0..target.height() {
+            for (x, pixel) in target.row_mut(y).iter_mut().enumerate() {
+                let color = format.unpack(*pixel);
+                let offset = (threshold(x, y) - 0.5) * self.strength;
+                let nudge = |value: u8, spread: f32| {
+                    (value as f32 + offset * spread).round().clamp(0.0, 255.0) as u8
+                };
+                let nudged = Color::new(
+                    nudge(color.r, spread[0]),
+                    nudge(color.g, spread[1]),
+                    nudge(color.b, spread[2]),
+                    color.a,
+                );
+                *pixel = format.pack(self.quantize.apply(nudged));
+            }
+        }
+    }
+
+    /// Pushes each pixel's quantization error onto its unvisited neighbours,
+    /// going left to right on even rows and right to left on odd ones.
+    fn floyd_steinberg(&self, target: &mut FrameView) {
+        let format = target.format();
+        let width = target.width();
+
+        // Accumulated error for this row and the next, one RGB triple per pixel
+        let mut current = vec![[0.0f32; 3]; width + 2];
+        let mut next = vec![[0.0f32; 3]; width + 2];
+
+        for y in 0..target.height() {
+            let row = target.row_mut(y);
+            let reverse = y % 2 == 1;
+            // Forward and back are along the direction of travel
+            let forward: isize = if reverse { -1 } else { 1 };
+
+            for i in 0..width {
+                let x = if reverse { width - 1 - i } else { i };
+                // Error slots are offset by one so both edges have a neighbour
+                let slot = x + 1;
+
+                let color = format.unpack(row[x]);
+                let channels = [color.r, color.g, color.b];
+                let wanted = [0, 1, 2].map(|c| channels[c] as f32 + current[slot][c]);
+                let clamped = wanted.map(|v| v.round().clamp(0.0, 255.0) as u8);
+                let quantized = self
+                    .quantize
+                    .apply(Color::new(clamped[0], clamped[1], clamped[2], color.a));
+                row[x] = format.pack(quantized);
+
+                let got = [quantized.r, quantized.g, quantized.b];
+                let ahead = slot.wrapping_add_signed(forward);
+                let behind = slot.wrapping_add_signed(-forward);
+                for c in 0..3 {
+                    let error = (wanted[c] - got[c] as f32) * self.strength;
+                    current[ahead][c] += error * 7.0 / 16.0;
+                    next[behind][c] += error * 3.0 / 16.0;
+                    next[slot][c] += error * 5.0 / 16.0;
+                    next[ahead][c] += error * 1.0 / 16.0;
+                }
+            }
+
+            std::mem::swap(&mut current, &mut next);
+            next.fill([0.0; 3]);
+        }
+    }
+}
+
+/// Returns the Bayer threshold (0.0 to 1.0) of pixel `(x, y)` in a matrix
+/// of side `2^order`.
+///
+/// The matrix value interleaves the bits of `x ^ y` and `y`, lowest
+/// coordinate bits first, which is the recursive Bayer construction
+/// unrolled.
+fn bayer(x: usize, y: usize, order: u32) -> f32 {
+    let mut value = 0;
+    for bit in 0..order {
+        let xb = (x >> bit) & 1;
+        let yb = (y >> bit) & 1;
+        let shift = 2 * (order - 1 - bit);
+        value |= (((xb ^ yb) << 1) | yb) << shift;
+    }
+    (value as f32 + 0.5) / (1 << (2 * order)) as f32
+}
+
+/// Returns a tiling blue-noise threshold map (0.0 to 1.0), built once with
+/// the void-and-cluster method.
+fn blue_noise() -> &'static [f32] {
+    static NOISE: OnceLock<Vec<f32>> = OnceLock::new();
+    NOISE.get_or_init(|| {
+        let ranks = void_and_cluster(BLUE_NOISE_SIZE, 1.5);
+        let count = ranks.len() as f32;
+        ranks.iter().map(|&r| (r as f32 + 0.5) / count).collect()
+    })
+}
+
+/// Ranks every pixel of a `size` x `size` tile so that the first `n` ranks
+/// are spread as evenly as possible for every `n` (Ulichney, 1993).
+fn void_and_cluster(size: usize, sigma: f32) -> Vec<usize> {
+    let count = size * size;
+
+    // Gaussian weight between two pixels, with wrap-around distances
+    let mut kernel = vec![0.0f32; count];
+    for dy in 0..size {
+        for dx in 0..size {
+            let wx = dx.min(size - dx) as f32;
+            let wy = dy.min(size - dy) as f32;
+            kernel[dy * size + dx] = (-(wx * wx + wy * wy) / (2.0 * sigma * sigma)).exp();
+        }
+    }
+
+    // `energy[p]` is how crowded the set pixels around `p` are
+    let toggle = |energy: &mut [f32], p: usize, sign: f32| {
+        let (px, py) = (p % size, p / size);
+        for y in 0..size {
+            for x in 0..size {
+                let dx = (x + size - px) % size;
+                let dy = (y + size - py) % size;
+                energy[y * size + x] += sign * kernel[dy * size + dx];
+            }
+        }
+    };
+    let tightest_cluster = |pattern: &[bool], energy: &[f32]| {
+        (0..count)
+            .filter(|&p| pattern[p])
+            .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+    let largest_void = |pattern: &[bool], energy: &[f32]| {
+        (0..count)
+            .filter(|&p| !pattern[p])
+            .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+
+    // 1. A sparse random pattern, relaxed by moving the most crowded point
+    // into the emptiest spot until that stops changing anything
+    let mut rng = 0x2545_F491u32;
+    let mut pattern = vec![false; count];
+    let mut energy = vec![0.0f32; count];
+    let mut ones = 0;
+    while ones < count / 10 {
+        rng ^= rng << 13;
+        rng ^= rng >> 17;
+        rng ^= rng << 5;
+        let p = rng as usize % count;
+        if !pattern[p] {
+            pattern[p] = true;
+            toggle(&mut energy, p, 1.0);
+            ones += 1;
+        }
+    }
+    loop {
+        let cluster = tightest_cluster(&pattern, &energy);
+        pattern[cluster] = false;
+        toggle(&mut energy, cluster, -1.0);
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        toggle(&mut energy, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; count];
+
+    // 2. Rank the initial points by removing the most crowded first
+    let (mut removed, mut removed_energy) = (pattern.clone(), energy.clone());
+    for rank in (0..ones).rev() {
+        let cluster = tightest_cluster(&removed, &removed_energy);
+        removed[cluster] = false;
+        toggle(&mut removed_energy, cluster, -1.0);
+        ranks[cluster] = rank;
+    }
+
+    // 3. Rank the rest by filling the emptiest spot each time
+    for rank in ones..count {
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        toggle(&mut energy, void, 1.0);
+        ranks[void] = rank;
+    }
+
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    /// Fills a frame with one gray and returns the average of the red
+    /// channel after dithering to 1 bit.
+    fn dithered_average(method: DitherMethod, gray: u8) -> f32 {
+        let mut fb = Framebuffer::new(32, 32, PixelFormat::Argb32);
+        fb.clear(Color::new(gray, gray, gray, 255));
+        let bits = Quantize::Bits {
+            red: 1,
+            green: 1,
+            blue: 1,
+        };
+        Dither::new(method, bits).apply(&mut fb.view());
+
+        let mut sum = 0.0;
+        for y in 0..32 {
+            for x in 0..32 {
+                let r = fb.get_color(x, y).unwrap().r;
+                assert!(r == 0 || r == 255);
+                sum += r as f32;
+            }
+        }
+        sum / (32.0 * 32.0)
+    }
+
+    #[test]
+    fn test_quantize_bits() {
+        let color = Quantize::RGB565.apply(Color::new(100, 100, 100, 7));
+        assert_eq!(color, Color::new(99, 101, 99, 7));
+    }
+
+    #[test]
+    fn test_bayer_matrix() {
+        let order = |x, y| (bayer(x, y, 1) * 4.0) as u32;
+        assert_eq!(
+            [order(0, 0), order(1, 0), order(0, 1), order(1, 1)],
+            [0, 2, 3, 1]
+        );
+
+        // Every threshold of the 8x8 matrix is used exactly once
+        let mut seen = [false; 64];
+        for y in 0..8 {
+            for x in 0..8 {
+                seen[(bayer(x, y, 3) * 64.0) as usize] = true;
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_blue_noise_is_a_permutation() {
+        let mut ranks = void_and_cluster(8, 1.5);
+        ranks.sort();
+        assert_eq!(ranks, (0..64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_dithering_preserves_average_brightness() {
+        // Plain quantization snaps mid gray to one side
+        assert!((dithered_average(DitherMethod::None, 96) - 0.0).abs() < 1.0);
+
+        for method in [
+            DitherMethod::Bayer4,
+            DitherMethod::Bayer8,
+            DitherMethod::BlueNoise,
+            DitherMethod::FloydSteinberg,
+        ] {
+            let average = dithered_average(method, 96);
+            assert!((average - 96.0).abs() < 10.0, "{method:?}: {average}");
+        }
+    }
+
+    #[test]
+    fn test_dither_to_palette() {
+        let palette = Palette::from_colors(&[Color::BLACK, Color::WHITE]);
+        let mut fb = Framebuffer::new(4, 4, PixelFormat::Rgba8);
+        fb.clear(Color::new(128, 128, 128, 255));
+        Dither::new(DitherMethod::Bayer2, Quantize::Palette(Box::new(palette)))
+            .apply(&mut fb.view());
+
+        let whites = (0..16)
+            .filter(|i| fb.get_color(i % 4, i / 4) == Some(Color::WHITE))
+            .count();
+        assert_eq!(whites, 8);
+    }
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!("Bayer4".parse(), Ok(DitherMethod::Bayer4));
+        assert_eq!("floyd-steinberg".parse(), Ok(DitherMethod::FloydSteinberg));
+        assert!("halftone".parse::<DitherMethod>().is_err());
+    }
+}
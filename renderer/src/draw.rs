@@ -0,0 +1,409 @@
+//! 2D drawing on a `FrameView`: lines, rectangles, circles, polygons and sprites.
+//!
+//! Coordinates are signed so shapes can hang off any edge; everything is
+//! clipped to the view. Colors with an alpha below 255 are blended over
+//! what is already there.
+
+use crate::{color::Color, framebuffer::FrameView, texture::Texture};
+
+/// Blends `color` into the pixel at (x, y) according to its alpha.
+pub fn blend_pixel(target: &mut FrameView, x: i32, y: i32, color: Color) {
+    if x < 0 || y < 0 {
+        return;
+    }
+    let (x, y) = (x as usize, y as usize);
+    match color.a {
+        0 => {}
+        255 => target.put(x, y, color),
+        _ => {
+            if let Some(pixel) = target.get(x, y) {
+                let below = target.format().unpack(pixel);
+                target.put(x, y, color.over(&below));
+            }
+        }
+    }
+}
+
+/// Blends `color` with its alpha scaled by `coverage` (0.0 to 1.0).
+fn plot(target: &mut FrameView, x: i32, y: i32, color: Color, coverage: f32) {
+    let alpha = (color.a as f32 * math::clamp(coverage, 0.0, 1.0)).round() as u8;
+    blend_pixel(target, x, y, Color::new(color.r, color.g, color.b, alpha));
+}
+
+/// Clips the segment to the view (Liang–Barsky). Returns `None` if it is
+/// entirely outside.
+fn clip_line(
+    target: &FrameView,
+    (x0, y0): (f32, f32),
+    (x1, y1): (f32, f32),
+) -> Option<((f32, f32), (f32, f32))> {
+    let (max_x, max_y) = (target.width() as f32 - 0.5, target.height() as f32 - 0.5);
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+
+    for (p, q) in [
+        (-dx, x0 + 0.5),
+        (dx, max_x - x0),
+        (-dy, y0 + 0.5),
+        (dy, max_y - y0),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    (t0 <= t1).then_some(((x0 + t0 * dx, y0 + t0 * dy), (x0 + t1 * dx, y0 + t1 * dy)))
+}
+
+/// Draws a one pixel wide line from (x0, y0) to (x1, y1), both ends included
+/// (Bresenham).
+pub fn line(target: &mut FrameView, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+    let inside = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && (x as usize) < target.width() && (y as usize) < target.height()
+    };
+
+    // Long lines that leave the view are shortened first so we never walk
+    // thousands of invisible pixels
+    let (mut x, mut y, x1, y1) = if inside(x0, y0) && inside(x1, y1) {
+        (x0, y0, x1, y1)
+    } else {
+        let p0 = (x0 as f32, y0 as f32);
+        let p1 = (x1 as f32, y1 as f32);
+        let Some((a, b)) = clip_line(target, p0, p1) else {
+            return;
+        };
+        let r = |v: f32| v.round() as i32;
+        (r(a.0), r(a.1), r(b.0), r(b.1))
+    };
+
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        blend_pixel(target, x, y, color);
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// Draws an anti-aliased line between two points in pixel coordinates,
+/// where (0.0, 0.0) is the center of the top-left pixel (Xiaolin Wu).
+pub fn line_aa(target: &mut FrameView, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+    let Some(((mut x0, mut y0), (mut x1, mut y1))) = clip_line(target, (x0, y0), (x1, y1)) else {
+        return;
+    };
+
+    // Walk along the longer axis, one pixel per step
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        (x0, y0, x1, y1) = (y0, x0, y1, x1);
+    }
+    if x0 > x1 {
+        (x0, y0, x1, y1) = (x1, y1, x0, y0);
+    }
+
+    let dx = x1 - x0;
+    let gradient = if dx == 0.0 { 0.0 } else { (y1 - y0) / dx };
+    let mut put = |a: i32, b: i32, coverage: f32| {
+        if steep {
+            plot(target, b, a, color, coverage);
+        } else {
+            plot(target, a, b, color, coverage);
+        }
+    };
+
+    let (start, end) = (x0.round() as i32, x1.round() as i32);
+    for x in start..=end {
+        let y = y0 + gradient * (x as f32 - x0);
+        let below = y.floor();
+        let frac = y - below;
+        put(x, below as i32, 1.0 - frac);
+        put(x, below as i32 + 1, frac);
+    }
+}
+
+/// Draws the one pixel wide outline of a `width` x `height` rectangle.
+pub fn rect(target: &mut FrameView, x: i32, y: i32, width: u32, height: u32, color: Color) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (right, bottom) = (x + width as i32 - 1, y + height as i32 - 1);
+    fill_rect(target, x, y, width, 1, color);
+    fill_rect(target, x, bottom, width, 1, color);
+    fill_rect(target, x, y + 1, 1, height.saturating_sub(2), color);
+    fill_rect(target, right, y + 1, 1, height.saturating_sub(2), color);
+}
+
+/// Fills a `width` x `height` rectangle.
+pub fn fill_rect(target: &mut FrameView, x: i32, y: i32, width: u32, height: u32, color: Color) {
+    let x_end = (x as i64 + width as i64).clamp(0, target.width() as i64) as usize;
+    let y_end = (y as i64 + height as i64).clamp(0, target.height() as i64) as usize;
+    let (x, y) = (x.max(0) as usize, y.max(0) as usize);
+    if x >= x_end || y >= y_end {
+        return;
+    }
+
+    if color.a == 255 {
+        let pixel = target.format().pack(color);
+        for row in y..y_end {
+            target.row_mut(row)[x..x_end].fill(pixel);
+        }
+    } else {
+        for row in y..y_end {
+            for col in x..x_end {
+                blend_pixel(target, col as i32, row as i32, color);
+            }
+        }
+    }
+}
+
+/// Fills a horizontal run of pixels from `x0` to `x1`, both included.
+fn span(target: &mut FrameView, x0: i32, x1: i32, y: i32, color: Color) {
+    if x1 >= x0 {
+        fill_rect(target, x0, y, (x1 - x0 + 1) as u32, 1, color);
+    }
+}
+
+/// Draws the outline of a circle (midpoint algorithm).
+pub fn circle(target: &mut FrameView, cx: i32, cy: i32, radius: u32, color: Color) {
+    let radius = radius as i32;
+    let (mut x, mut y) = (radius, 0);
+    let mut err = 1 - radius;
+
+    while x >= y {
+        // Each step gives one point per octant. Skipping repeats avoids
+        // blending the same pixel twice on the diagonals and axes.
+        let mut points = [
+            (cx + x, cy + y),
+            (cx + y, cy + x),
+            (cx - y, cy + x),
+            (cx - x, cy + y),
+            (cx - x, cy - y),
+            (cx - y, cy - x),
+            (cx + y, cy - x),
+            (cx + x, cy - y),
+        ];
+        points.sort_unstable();
+        let mut last = None;
+        for p in points {
+            if last != Some(p) {
+                blend_pixel(target, p.0, p.1, color);
+                last = Some(p);
+            }
+        }
+
+        y += 1;
+        if err < 0 {
+            err += 2 * y + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Fills a circle: every pixel whose center is within `radius` + 0.5 of the center.
+pub fn fill_circle(target: &mut FrameView, cx: i32, cy: i32, radius: u32, color: Color) {
+    let r = radius as f32 + 0.5;
+    for dy in -(radius as i32)..=radius as i32 {
+        let half = (r * r - (dy * dy) as f32).sqrt() as i32;
+        span(target, cx - half, cx + half, cy + dy, color);
+    }
+}
+
+/// Fills a polygon given by its corners, in pixel coordinates where (0.0, 0.0)
+/// is the top-left corner of the view.
+///
+/// A pixel is filled if its center is inside (even-odd rule), so polygons
+/// sharing an edge never fill the same pixel twice.
+pub fn fill_polygon(target: &mut FrameView, points: &[(f32, f32)], color: Color) {
+    if points.len() < 3 {
+        return;
+    }
+
+    let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    let first = (min_y - 0.5).ceil().max(0.0) as i32;
+    let last = ((max_y - 0.5).ceil() as i32).min(target.height() as i32);
+
+    let mut crossings = Vec::new();
+    for y in first..last {
+        let sample = y as f32 + 0.5;
+        crossings.clear();
+
+        // Each edge is half-open in y so a shared vertex is counted once
+        for (i, &(ax, ay)) in points.iter().enumerate() {
+            let (bx, by) = points[(i + 1) % points.len()];
+            if (ay <= sample) != (by <= sample) {
+                crossings.push(ax + (sample - ay) / (by - ay) * (bx - ax));
+            }
+        }
+        crossings.sort_unstable_by(f32::total_cmp);
+
+        for pair in crossings.chunks_exact(2) {
+            let x0 = (pair[0] - 0.5).ceil() as i32;
+            let x1 = (pair[1] - 0.5).ceil() as i32 - 1;
+            span(target, x0, x1, y, color);
+        }
+    }
+}
+
+/// Draws `sprite` with its top-left corner at (x, y), blending each pixel
+/// by its alpha.
+pub fn blit(target: &mut FrameView, sprite: &Texture, x: i32, y: i32) {
+    // Only walk the part of the sprite that lands inside the view
+    let sx0 = (-x).max(0) as u32;
+    let sy0 = (-y).max(0) as u32;
+    let sx1 = (target.width() as i64 - x as i64).clamp(0, sprite.width() as i64) as u32;
+    let sy1 = (target.height() as i64 - y as i64).clamp(0, sprite.height() as i64) as u32;
+
+    for sy in sy0..sy1 {
+        for sx in sx0..sx1 {
+            if let Some(color) = sprite.get(sx, sy) {
+                blend_pixel(target, x + sx as i32, y + sy as i32, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    const WHITE: Color = Color::new(255, 255, 255, 255);
+
+    fn canvas(width: usize, height: usize) -> Framebuffer {
+        let mut fb = Framebuffer::new(width, height, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+        fb
+    }
+
+    /// The lit pixels, `#` for white, `.` for black and `+` for anything else.
+    fn ascii(fb: &Framebuffer) -> Vec<String> {
+        (0..fb.height())
+            .map(|y| {
+                (0..fb.width())
+                    .map(|x| match fb.get(x, y) {
+                        Some(0xFFFFFFFF) => '#',
+                        Some(0xFF000000) => '.',
+                        _ => '+',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_line_includes_both_ends() {
+        let mut fb = canvas(5, 3);
+        line(&mut fb.view(), 0, 0, 4, 2, WHITE);
+        assert_eq!(ascii(&fb), ["#....", ".##..", "...##"]);
+    }
+
+    #[test]
+    fn test_line_is_clipped() {
+        let mut fb = canvas(4, 3);
+        line(&mut fb.view(), -1_000_000, 1, 1_000_000, 1, WHITE);
+        assert_eq!(ascii(&fb), ["....", "####", "...."]);
+
+        // Entirely outside: nothing happens
+        line(&mut fb.view(), -5, -5, -1, 10, WHITE);
+        assert_eq!(ascii(&fb), ["....", "####", "...."]);
+    }
+
+    #[test]
+    fn test_line_aa_splits_coverage() {
+        let mut fb = canvas(4, 3);
+        line_aa(&mut fb.view(), 0.0, 1.0, 3.0, 1.0, WHITE);
+        assert_eq!(ascii(&fb), ["....", "####", "...."]);
+
+        let mut fb = canvas(4, 3);
+        line_aa(&mut fb.view(), 0.0, 0.5, 3.0, 0.5, WHITE);
+        assert_eq!(ascii(&fb), ["++++", "++++", "...."]);
+        assert_eq!(fb.get(0, 0), Some(0xFF808080));
+    }
+
+    #[test]
+    fn test_rects() {
+        let mut fb = canvas(5, 4);
+        rect(&mut fb.view(), 0, 0, 4, 3, WHITE);
+        assert_eq!(ascii(&fb), ["####.", "#..#.", "####.", "....."]);
+
+        let mut fb = canvas(4, 3);
+        fill_rect(&mut fb.view(), -2, 1, 4, 10, WHITE);
+        assert_eq!(ascii(&fb), ["....", "##..", "##.."]);
+    }
+
+    #[test]
+    fn test_circles() {
+        let mut fb = canvas(7, 7);
+        circle(&mut fb.view(), 3, 3, 2, WHITE);
+        assert_eq!(
+            ascii(&fb),
+            [
+                ".......", "..###..", ".#...#.", ".#...#.", ".#...#.", "..###..", "......."
+            ]
+        );
+
+        let mut fb = canvas(5, 5);
+        fill_circle(&mut fb.view(), 2, 2, 1, WHITE);
+        assert_eq!(ascii(&fb), [".....", ".###.", ".###.", ".###.", "....."]);
+    }
+
+    #[test]
+    fn test_fill_polygon() {
+        let mut fb = canvas(4, 4);
+        fill_polygon(&mut fb.view(), &[(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)], WHITE);
+        assert_eq!(ascii(&fb), ["###.", "##..", "#...", "...."]);
+
+        // Two halves of a square cover it exactly once
+        let mut fb = canvas(4, 4);
+        let half = Color::new(255, 255, 255, 128);
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+        fill_polygon(&mut fb.view(), &[square[0], square[1], square[2]], half);
+        fill_polygon(&mut fb.view(), &[square[0], square[2], square[3]], half);
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(fb.get(x, y), Some(0xFF808080));
+            }
+        }
+    }
+
+    #[test]
+    fn test_blit_blends_and_clips() {
+        let sprite = Texture::new(2, 1, vec![255, 0, 0, 255, 255, 255, 255, 128]);
+        let mut fb = canvas(3, 1);
+        blit(&mut fb.view(), &sprite, 0, 0);
+        assert_eq!(fb.get(0, 0), Some(0xFFFF0000));
+        assert_eq!(fb.get(1, 0), Some(0xFF808080));
+
+        // Hanging off the right edge
+        blit(&mut fb.view(), &sprite, 2, 0);
+        assert_eq!(fb.get(2, 0), Some(0xFFFF0000));
+        blit(&mut fb.view(), &sprite, -5, -5);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::color::Color;
+
+/// How the fog thickens with distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FogMode {
+    /// No fog at all.
+    None,
+    /// No fog before `start`, fully fogged at `end` and beyond.
+    Linear { start: f32, end: f32 },
+    /// Fog thickens as `1 - e^(-density * distance)`.
+    Exponential { density: f32 },
+}
+
+/// Distance-based fog and Doom-style light diminishing.
+///
+/// Diminishing darkens surfaces the further away they are; fog then blends
+/// the result towards `color`. Both are applied by `shade`.
+#[derive(Clone, Copy, Debug)]
+pub struct Fog {
+    pub mode: FogMode,
+
+    /// The color far-away surfaces fade into.
+    pub color: Color,
+
+    /// How much light is lost per unit of distance (0.0 disables diminishing).
+    pub diminish: f32,
+
+    /// The darkest a surface can get from diminishing alone (0.0 to 1.0).
+    pub min_light: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            mode: FogMode::None,
+            color: Color::new(0, 0, 0, 255),
+            diminish: 0.0,
+            min_light: 0.0,
+        }
+    }
+}
+
+impl Fog {
+    /// Returns `true` if shading would leave every color untouched.
+    pub fn is_disabled(&self) -> bool {
+        self.mode == FogMode::None && self.diminish <= 0.0
+    }
+
+    /// Returns how much of the fog color covers a surface at `distance` (0.0 to 1.0).
+    pub fn density_at(&self, distance: f32) -> f32 {
+        match self.mode {
+            FogMode::None => 0.0,
+            FogMode::Linear { start, end } => {
+                if end <= start {
+                    return if distance >= end { 1.0 } else { 0.0 };
+                }
+                math::clamp((distance - start) / (end - start), 0.0, 1.0)
+            }
+            FogMode::Exponential { density } => 1.0 - (-density * distance).exp(),
+        }
+    }
+
+    /// Returns the light multiplier for a surface at `distance` (0.0 to 1.0).
+    pub fn light_at(&self, distance: f32) -> f32 {
+        let floor = math::clamp(self.min_light, 0.0, 1.0);
+        math::clamp(1.0 - distance * self.diminish, floor, 1.0)
+    }
+
+    /// Applies light diminishing and fog to a color seen at `distance`.
+    pub fn shade(&self, color: Color, distance: f32) -> Color {
+        if self.is_disabled() {
+            return color;
+        }
+        let lit = color.darkened(self.light_at(distance));
+        lit.lerp(&self.color, self.density_at(distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_density() {
+        let fog = Fog {
+            mode: FogMode::Linear {
+                start: 2.0,
+                end: 6.0,
+            },
+            ..Fog::default()
+        };
+        assert_eq!(fog.density_at(1.0), 0.0);
+        assert_eq!(fog.density_at(4.0), 0.5);
+        assert_eq!(fog.density_at(10.0), 1.0);
+    }
+
+    #[test]
+    fn test_exponential_density() {
+        let fog = Fog {
+            mode: FogMode::Exponential { density: 0.5 },
+            ..Fog::default()
+        };
+        assert_eq!(fog.density_at(0.0), 0.0);
+        assert!(fog.density_at(2.0) > fog.density_at(1.0));
+        assert!(fog.density_at(100.0) > 0.99);
+    }
+
+    #[test]
+    fn test_diminishing_respects_min_light() {
+        let fog = Fog {
+            diminish: 0.1,
+            min_light: 0.25,
+            ..Fog::default()
+        };
+        assert!((fog.light_at(5.0) - 0.5).abs() < 1e-6);
+        assert_eq!(fog.light_at(50.0), 0.25);
+    }
+
+    #[test]
+    fn test_shade_fades_to_fog_color() {
+        let fog = Fog {
+            mode: FogMode::Linear {
+                start: 0.0,
+                end: 1.0,
+            },
+            color: Color::new(10, 20, 30, 255),
+            ..Fog::default()
+        };
+        let far = fog.shade(Color::new(200, 200, 200, 255), 5.0);
+        assert_eq!(far.to_array(), [10, 20, 30, 255]);
+    }
+}
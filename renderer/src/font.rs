@@ -0,0 +1,564 @@
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use crate::{color::Color, draw, framebuffer::FrameView, texture::Texture};
+
+/// Where a character sits on the glyph sheet and how it is placed on a line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Offset from the pen position to the top-left corner of the glyph.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// How far the pen moves after drawing this glyph.
+    pub advance: i32,
+}
+
+/// A bitmap font: a glyph sheet plus where each character is on it.
+///
+/// Only the sheet's alpha channel decides coverage. Its colors are multiplied
+/// by the text color, so white glyphs draw in exactly the requested color.
+#[derive(Clone, Debug)]
+pub struct Font {
+    sheet: Texture,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), i32>,
+    line_height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A drop shadow drawn behind the text.
+#[derive(Clone, Copy, Debug)]
+pub struct Shadow {
+    pub color: Color,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub color: Color,
+    /// `x` passed to `Font::draw` is the left edge, center or right edge of every line.
+    pub align: Align,
+    /// Wrap at word boundaries so no line is wider than this many pixels.
+    pub max_width: Option<u32>,
+    pub shadow: Option<Shadow>,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::new(255, 255, 255, 255),
+            align: Align::Left,
+            max_width: None,
+            shadow: None,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    Image(image::ImageError),
+    /// A malformed BMFont descriptor. `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "cannot read font: {e}"),
+            FontError::Image(e) => write!(f, "cannot load glyph sheet: {e}"),
+            FontError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            FontError::Image(e) => Some(e),
+            FontError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+impl From<image::ImageError> for FontError {
+    fn from(e: image::ImageError) -> Self {
+        FontError::Image(e)
+    }
+}
+
+/// The built-in font: printable ASCII, 3x5 pixels in a 4x6 cell.
+///
+/// Each entry is one glyph, 5 rows of 3 bits, top row in the highest bits.
+const TINY_GLYPHS: [u16; 95] = [
+    0x0000, 0x2482, 0x5A00, 0x5F7D, 0x3C9E, 0x52A5, 0x2AAB, 0x2400, // ' ' to '\''
+    0x1491, 0x4494, 0x0AA8, 0x05D0, 0x0014, 0x01C0, 0x0002, 0x12A4, // '(' to '/'
+    0x7B6F, 0x2C97, 0x62A7, 0x628E, 0x5BC9, 0x798E, 0x39EF, 0x7292, // '0' to '7'
+    0x7BEF, 0x7BCE, 0x0410, 0x0414, 0x1511, 0x0E38, 0x4454, 0x6282, // '8' to '?'
+    0x2BE3, 0x2BED, 0x6BAE, 0x3923, 0x6B6E, 0x79A7, 0x79A4, 0x396B, // '@' to 'G'
+    0x5BED, 0x7497, 0x126A, 0x5BAD, 0x4927, 0x5FED, 0x6B6D, 0x2B6A, // 'H' to 'O'
+    0x6BA4, 0x2B73, 0x6BAD, 0x388E, 0x7492, 0x5B6F, 0x5B6A, 0x5BFD, // 'P' to 'W'
+    0x5AAD, 0x5A92, 0x72A7, 0x3493, 0x4889, 0x6496, 0x2A00, 0x0007, // 'X' to '_'
+    0x4400, 0x076B, 0x4D6E, 0x0723, 0x176B, 0x05E3, 0x15D2, 0x075E, // '`' to 'g'
+    0x4D6D, 0x2092, 0x106A, 0x4BB5, 0x6497, 0x0BED, 0x0D6D, 0x056A, // 'h' to 'o'
+    0x0D74, 0x0759, 0x0724, 0x078E, 0x2E91, 0x0B6B, 0x0B6A, 0x0B7D, // 'p' to 'w'
+    0x0A95, 0x0B5E, 0x0EE7, 0x3593, 0x2492, 0x64D6, 0x0CC0, // 'x' to '~'
+];
+
+impl Font {
+    /// Builds a fixed-width font from a sheet of equal cells.
+    ///
+    /// `chars` lists the characters in the order their cells appear on the
+    /// sheet, left to right and then top to bottom.
+    pub fn fixed(sheet: Texture, cell_width: u32, cell_height: u32, chars: &str) -> Self {
+        let columns = sheet.width() / cell_width.max(1);
+        let rows = sheet.height() / cell_height.max(1);
+
+        let glyphs = chars
+            .chars()
+            .take((columns * rows) as usize)
+            .enumerate()
+            .map(|(i, c)| {
+                let glyph = Glyph {
+                    x: i as u32 % columns * cell_width,
+                    y: i as u32 / columns * cell_height,
+                    width: cell_width,
+                    height: cell_height,
+                    x_offset: 0,
+                    y_offset: 0,
+                    advance: cell_width as i32,
+                };
+                (c, glyph)
+            })
+            .collect();
+
+        Self {
+            sheet,
+            glyphs,
+            kerning: HashMap::new(),
+            line_height: cell_height,
+        }
+    }
+
+    /// A tiny 3x5 font covering printable ASCII, for debug text without assets.
+    pub fn builtin() -> Self {
+        const COLUMNS: u32 = 16;
+        let rows = (TINY_GLYPHS.len() as u32).div_ceil(COLUMNS);
+        let (width, height) = (COLUMNS * 4, rows * 6);
+
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        for (i, bits) in TINY_GLYPHS.iter().enumerate() {
+            let (cx, cy) = (i as u32 % COLUMNS * 4, i as u32 / COLUMNS * 6);
+            for bit in 0..15 {
+                if bits & (1 << (14 - bit)) != 0 {
+                    let (x, y) = (cx + bit % 3, cy + bit / 3);
+                    let start = ((y * width + x) * 4) as usize;
+                    pixels[start..start + 4].fill(255);
+                }
+            }
+        }
+
+        let sheet = Texture::new(width, height, pixels);
+        let chars: String = (' '..='~').collect();
+        Self::fixed(sheet, 4, 6, &chars)
+    }
+
+    /// Parses an AngelCode BMFont text descriptor for a single-page font
+    /// whose page is `sheet`.
+    pub fn parse_bmfont(sheet: Texture, descriptor: &str) -> Result<Self, FontError> {
+        let mut font = Self {
+            sheet,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            line_height: 0,
+        };
+
+        for (index, line) in descriptor.lines().enumerate() {
+            let error = |message: String| FontError::Parse {
+                line: index + 1,
+                message,
+            };
+            let Some((tag, attrs)) = parse_tagged_line(line) else {
+                continue;
+            };
+            let get = |key: &str| -> Result<i32, FontError> {
+                let value = attrs
+                    .get(key)
+                    .ok_or_else(|| error(format!("{tag} is missing {key}")))?;
+                value
+                    .parse()
+                    .map_err(|_| error(format!("invalid {key}: {value}")))
+            };
+            let get_char = |key: &str| -> Result<char, FontError> {
+                let id = get(key)?;
+                char::from_u32(id as u32).ok_or_else(|| error(format!("invalid {key}: {id}")))
+            };
+
+            match tag {
+                "common" => font.line_height = get("lineHeight")?.max(0) as u32,
+                "char" => {
+                    let c = get_char("id")?;
+                    let glyph = Glyph {
+                        x: get("x")?.max(0) as u32,
+                        y: get("y")?.max(0) as u32,
+                        width: get("width")?.max(0) as u32,
+                        height: get("height")?.max(0) as u32,
+                        x_offset: get("xoffset")?,
+                        y_offset: get("yoffset")?,
+                        advance: get("xadvance")?,
+                    };
+                    if glyph.x + glyph.width > font.sheet.width()
+                        || glyph.y + glyph.height > font.sheet.height()
+                    {
+                        return Err(error(format!("glyph {c:?} lies outside the sheet")));
+                    }
+                    font.glyphs.insert(c, glyph);
+                }
+                "kerning" => {
+                    let pair = (get_char("first")?, get_char("second")?);
+                    font.kerning.insert(pair, get("amount")?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(font)
+    }
+
+    /// Loads a BMFont text descriptor and the sheet its first page names,
+    /// relative to the descriptor.
+    pub fn load_bmfont(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let path = path.as_ref();
+        let descriptor = fs::read_to_string(path)?;
+
+        let page =
+            descriptor
+                .lines()
+                .enumerate()
+                .find_map(|(i, line)| match parse_tagged_line(line) {
+                    Some(("page", attrs)) => Some((i, attrs.get("file").map(|f| f.to_string()))),
+                    _ => None,
+                });
+        let file = match page {
+            Some((_, Some(file))) => file,
+            Some((i, None)) => {
+                return Err(FontError::Parse {
+                    line: i + 1,
+                    message: "page is missing file".to_string(),
+                });
+            }
+            None => {
+                return Err(FontError::Parse {
+                    line: 1,
+                    message: "no page found".to_string(),
+                });
+            }
+        };
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let sheet = Texture::load(dir.join(file))?;
+        Self::parse_bmfont(sheet, &descriptor)
+    }
+
+    /// The distance between the tops of two lines, in pixels.
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    /// Returns the glyph drawn for `c`. Unknown characters fall back to `?`.
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Returns the advance of a single line of text, kerning included.
+    pub fn text_width(&self, line: &str) -> i32 {
+        let mut width = 0;
+        let mut prev = None;
+        for c in line.chars() {
+            width += self.kern(prev, c) + self.glyph(c).map_or(0, |g| g.advance);
+            prev = Some(c);
+        }
+        width
+    }
+
+    fn kern(&self, prev: Option<char>, c: char) -> i32 {
+        prev.and_then(|p| self.kerning.get(&(p, c)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Splits `text` into lines at newlines and, with a `max_width`, between words.
+    ///
+    /// A single word wider than `max_width` gets a line of its own and overflows.
+    pub fn wrap<'t>(&self, text: &'t str, max_width: Option<u32>) -> Vec<&'t str> {
+        let mut lines = Vec::new();
+
+        for paragraph in text.split('\n') {
+            let Some(max_width) = max_width else {
+                lines.push(paragraph);
+                continue;
+            };
+
+            // Byte range of the current line within the paragraph
+            let mut line: Option<(usize, usize)> = None;
+            for word in paragraph.split_whitespace() {
+                let start = word.as_ptr() as usize - paragraph.as_ptr() as usize;
+                let end = start + word.len();
+
+                line = Some(match line {
+                    None => (start, end),
+                    Some((line_start, line_end)) => {
+                        if self.text_width(&paragraph[line_start..end]) > max_width as i32 {
+                            lines.push(&paragraph[line_start..line_end]);
+                            (start, end)
+                        } else {
+                            (line_start, end)
+                        }
+                    }
+                });
+            }
+            lines.push(line.map_or("", |(start, end)| &paragraph[start..end]));
+        }
+
+        lines
+    }
+
+    /// Returns the width and height `text` takes up when drawn with `style`.
+    pub fn measure(&self, text: &str, style: &TextStyle) -> (u32, u32) {
+        let lines = self.wrap(text, style.max_width);
+        let width = lines
+            .iter()
+            .map(|line| self.text_width(line).max(0) as u32)
+            .max()
+            .unwrap_or(0);
+        (width, lines.len() as u32 * self.line_height)
+    }
+
+    /// Draws `text` with its first line's top at `y`. Pixels outside the view are skipped.
+    pub fn draw(&self, target: &mut FrameView, x: i32, y: i32, text: &str, style: &TextStyle) {
+        let lines = self.wrap(text, style.max_width);
+
+        let passes = style
+            .shadow
+            .map(|s| (s.dx, s.dy, s.color))
+            .into_iter()
+            .chain([(0, 0, style.color)]);
+        for (dx, dy, color) in passes {
+            for (i, line) in lines.iter().enumerate() {
+                let width = self.text_width(line);
+                let line_x = match style.align {
+                    Align::Left => x,
+                    Align::Center => x - width / 2,
+                    Align::Right => x - width,
+                };
+                let line_y = y + (i as u32 * self.line_height) as i32;
+                self.draw_line(target, line_x + dx, line_y + dy, line, color);
+            }
+        }
+    }
+
+    fn draw_line(&self, target: &mut FrameView, x: i32, y: i32, line: &str, color: Color) {
+        let mut pen = x;
+        let mut prev = None;
+        for c in line.chars() {
+            pen += self.kern(prev, c);
+            prev = Some(c);
+            let Some(glyph) = self.glyph(c) else {
+                continue;
+            };
+            self.draw_glyph(
+                target,
+                pen + glyph.x_offset,
+                y + glyph.y_offset,
+                glyph,
+                color,
+            );
+            pen += glyph.advance;
+        }
+    }
+
+    fn draw_glyph(&self, target: &mut FrameView, x: i32, y: i32, glyph: &Glyph, color: Color) {
+        for gy in 0..glyph.height {
+            for gx in 0..glyph.width {
+                let Some(Color { r, g, b, a }) = self.sheet.get(glyph.x + gx, glyph.y + gy) else {
+                    continue;
+                };
+                let scale = |sheet: u8, text: u8| (sheet as u32 * text as u32 / 255) as u8;
+                let ink = Color::new(
+                    scale(r, color.r),
+                    scale(g, color.g),
+                    scale(b, color.b),
+                    scale(a, color.a),
+                );
+                draw::blend_pixel(target, x + gx as i32, y + gy as i32, ink);
+            }
+        }
+    }
+}
+
+/// Splits a `tag key=value ...` line, as used by BMFont descriptors and HUD
+/// layouts, into its tag and attributes.
+///
+/// Values may be quoted to contain spaces.
+pub(crate) fn parse_tagged_line(line: &str) -> Option<(&str, HashMap<&str, &str>)> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    if tag.is_empty() {
+        return None;
+    }
+
+    let mut attrs = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let (value, after) = if let Some(quoted) = after.strip_prefix('"') {
+            quoted.split_once('"').unwrap_or((quoted, ""))
+        } else {
+            after.split_once(char::is_whitespace).unwrap_or((after, ""))
+        };
+        attrs.insert(key.trim(), value);
+        rest = after;
+    }
+
+    Some((tag, attrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    const RED: u32 = 0xFFFF0000;
+    const BLACK: u32 = 0xFF000000;
+
+    /// Renders the lit pixels of a view as `#` and `.`, one string per row.
+    fn ascii(fb: &Framebuffer) -> Vec<String> {
+        (0..fb.height())
+            .map(|y| {
+                (0..fb.width())
+                    .map(|x| {
+                        if fb.get(x, y) == Some(BLACK) {
+                            '.'
+                        } else {
+                            '#'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_draws_glyphs() {
+        let font = Font::builtin();
+        let mut fb = Framebuffer::new(8, 5, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+
+        let style = TextStyle::new(Color::new(255, 0, 0, 255));
+        font.draw(&mut fb.view(), 0, 0, "H1", &style);
+
+        assert_eq!(
+            ascii(&fb),
+            ["#.#..#..", "#.#.##..", "###..#..", "#.#..#..", "#.#.###."]
+        );
+        assert_eq!(fb.get(0, 0), Some(RED));
+        assert_eq!(font.text_width("H1"), 8);
+        assert_eq!(font.line_height(), 6);
+    }
+
+    #[test]
+    fn test_wrap_at_words() {
+        let font = Font::builtin();
+        // Every character is 4 pixels wide
+        assert_eq!(font.wrap("ab cd ef", Some(20)), ["ab cd", "ef"]);
+        assert_eq!(font.wrap("ab\n\ncd", None), ["ab", "", "cd"]);
+        assert_eq!(font.wrap("toolong x", Some(8)), ["toolong", "x"]);
+
+        let style = TextStyle {
+            max_width: Some(20),
+            ..TextStyle::default()
+        };
+        assert_eq!(font.measure("ab cd ef", &style), (20, 12));
+    }
+
+    #[test]
+    fn test_align_and_shadow() {
+        let font = Font::builtin();
+        let mut fb = Framebuffer::new(8, 6, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+
+        let style = TextStyle {
+            color: Color::new(255, 0, 0, 255),
+            align: Align::Right,
+            shadow: Some(Shadow {
+                color: Color::new(0, 0, 255, 255),
+                dx: 1,
+                dy: 1,
+            }),
+            ..TextStyle::default()
+        };
+        font.draw(&mut fb.view(), 8, 0, "_", &style);
+
+        // '_' is the bottom row of a 4 pixel cell ending at the right edge
+        assert_eq!(fb.get(4, 4), Some(RED));
+        assert_eq!(fb.get(6, 4), Some(RED));
+        assert_eq!(fb.get(7, 5), Some(0xFF0000FF));
+        assert_eq!(fb.get(3, 4), Some(BLACK));
+    }
+
+    #[test]
+    fn test_parse_bmfont() {
+        let sheet = Texture::new(8, 8, vec![255; 8 * 8 * 4]);
+        let descriptor = r#"info face="Tiny Sans" size=8
+common lineHeight=9 base=7 scaleW=8 scaleH=8 pages=1
+page id=0 file="tiny_0.png"
+chars count=2
+char id=65 x=0 y=0 width=4 height=6 xoffset=0 yoffset=1 xadvance=5 page=0 chnl=15
+char id=86 x=4 y=0 width=4 height=6 xoffset=-1 yoffset=1 xadvance=4 page=0 chnl=15
+kerning first=65 second=86 amount=-2"#;
+
+        let font = Font::parse_bmfont(sheet.clone(), descriptor).unwrap();
+        assert_eq!(font.line_height(), 9);
+        assert_eq!(font.glyph('V').unwrap().x_offset, -1);
+        assert_eq!(font.text_width("AV"), 5 - 2 + 4);
+        assert_eq!(font.text_width("VA"), 4 + 5);
+
+        let err = Font::parse_bmfont(sheet, "common lineHeight=9\nchar id=65 x=6 y=0 width=4")
+            .unwrap_err();
+        match err {
+            FontError::Parse { line, .. } => assert_eq!(line, 2),
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+}
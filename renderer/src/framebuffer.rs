@@ -0,0 +1,397 @@
+use crate::color::Color;
+
+/// How a `Color` is packed into a 32-bit pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Bytes in memory are R, G, B, A. This is what the `pixels` crate expects.
+    Rgba8,
+    /// Bytes in memory are B, G, R, A. Common for native window surfaces.
+    Bgra8,
+    /// The integer value is `0xAARRGGBB`, regardless of endianness.
+    Argb32,
+}
+
+impl PixelFormat {
+    /// Packs a color into a pixel of this format.
+    pub fn pack(self, color: Color) -> u32 {
+        let Color { r, g, b, a } = color;
+        match self {
+            PixelFormat::Rgba8 => u32::from_ne_bytes([r, g, b, a]),
+            PixelFormat::Bgra8 => u32::from_ne_bytes([b, g, r, a]),
+            PixelFormat::Argb32 => color.to_argb(),
+        }
+    }
+
+    /// Unpacks a pixel of this format back into a color.
+    pub fn unpack(self, pixel: u32) -> Color {
+        match self {
+            PixelFormat::Rgba8 => {
+                let [r, g, b, a] = pixel.to_ne_bytes();
+                Color::new(r, g, b, a)
+            }
+            PixelFormat::Bgra8 => {
+                let [b, g, r, a] = pixel.to_ne_bytes();
+                Color::new(r, g, b, a)
+            }
+            PixelFormat::Argb32 => Color::from_argb(pixel),
+        }
+    }
+
+    /// Converts a pixel from this format to `target`.
+    pub fn convert(self, pixel: u32, target: PixelFormat) -> u32 {
+        if self == target {
+            pixel
+        } else {
+            target.pack(self.unpack(pixel))
+        }
+    }
+}
+
+/// An axis-aligned rectangle in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the part of this rectangle that fits inside a `width` x `height` area.
+    pub fn clipped(&self, width: usize, height: usize) -> Rect {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        Rect {
+            x,
+            y,
+            width: self.width.min(width - x),
+            height: self.height.min(height - y),
+        }
+    }
+}
+
+/// An owned block of 32-bit pixels with an explicit format and row stride.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    stride: usize,
+    format: PixelFormat,
+    pixels: Vec<u32>,
+}
+
+impl Framebuffer {
+    /// Creates a tightly packed framebuffer (stride = width), cleared to zero.
+    pub fn new(width: usize, height: usize, format: PixelFormat) -> Self {
+        Self::with_stride(width, height, width, format)
+    }
+
+    /// Creates a framebuffer whose rows are `stride` pixels apart.
+    ///
+    /// # Panics
+    /// Panics if `stride` is smaller than `width`.
+    pub fn with_stride(width: usize, height: usize, stride: usize, format: PixelFormat) -> Self {
+        assert!(
+            stride >= width,
+            "stride ({stride}) must be at least width ({width})"
+        );
+        Self {
+            width,
+            height,
+            stride,
+            format,
+            pixels: vec![0; stride * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// The raw pixels, including any padding at the end of each row.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Returns the raw pixel at (x, y), or `None` if it is outside the buffer.
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y * self.stride + x])
+    }
+
+    /// Returns the color at (x, y), or `None` if it is outside the buffer.
+    pub fn get_color(&self, x: usize, y: usize) -> Option<Color> {
+        self.get(x, y).map(|p| self.format.unpack(p))
+    }
+
+    /// Returns a mutable view of the whole buffer.
+    pub fn view(&mut self) -> FrameView<'_> {
+        FrameView {
+            pixels: &mut self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            format: self.format,
+        }
+    }
+
+    /// Returns a mutable view of a region of the buffer (clipped to its bounds).
+    pub fn viewport(&mut self, rect: Rect) -> FrameView<'_> {
+        self.view().into_sub_view(rect)
+    }
+
+    /// Fills the whole buffer with `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.view().clear(color);
+    }
+
+    /// Copies the buffer into an RGBA8 byte frame, such as `Pixels::frame_mut`.
+    ///
+    /// # Panics
+    /// Panics if `frame` is not exactly `width * height * 4` bytes long.
+    pub fn write_rgba8(&self, frame: &mut [u8]) {
+        assert_eq!(
+            frame.len(),
+            self.width * self.height * 4,
+            "frame size mismatch"
+        );
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        for (y, out_row) in frame.chunks_exact_mut(self.width * 4).enumerate() {
+            let row = &self.pixels[y * self.stride..y * self.stride + self.width];
+            for (out, &pixel) in out_row.chunks_exact_mut(4).zip(row) {
+                let pixel = self.format.convert(pixel, PixelFormat::Rgba8);
+                out.copy_from_slice(&pixel.to_ne_bytes());
+            }
+        }
+    }
+}
+
+/// A mutable window into a `Framebuffer` (or any raw pixel slice).
+///
+/// Coordinates are relative to the top-left corner of the view, and all
+/// drawing is clipped to the view's bounds.
+pub struct FrameView<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+    height: usize,
+    stride: usize,
+    format: PixelFormat,
+}
+
+impl<'a> FrameView<'a> {
+    /// Wraps a tightly packed raw pixel slice.
+    ///
+    /// # Panics
+    /// Panics if `pixels` holds fewer than `width * height` pixels.
+    pub fn from_slice(
+        pixels: &'a mut [u32],
+        width: usize,
+        height: usize,
+        format: PixelFormat,
+    ) -> Self {
+        assert!(pixels.len() >= width * height, "pixel slice too small");
+        Self {
+            pixels,
+            width,
+            height,
+            stride: width,
+            format,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Returns a view of a region of this view, clipped to its bounds.
+    pub fn sub_view(&mut self, rect: Rect) -> FrameView<'_> {
+        FrameView {
+            pixels: &mut *self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            format: self.format,
+        }
+        .into_sub_view(rect)
+    }
+
+    fn into_sub_view(self, rect: Rect) -> FrameView<'a> {
+        let rect = rect.clipped(self.width, self.height);
+        let start = (rect.y * self.stride + rect.x).min(self.pixels.len());
+        FrameView {
+            pixels: &mut self.pixels[start..],
+            width: rect.width,
+            height: rect.height,
+            stride: self.stride,
+            format: self.format,
+        }
+    }
+
+    /// Returns the raw pixel at (x, y), or `None` if it is outside the view.
+    pub fn get(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y * self.stride + x])
+    }
+
+    /// Writes a raw pixel, already packed in this view's format. Out of bounds writes are ignored.
+    pub fn set(&mut self, x: usize, y: usize, pixel: u32) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.stride + x] = pixel;
+        }
+    }
+
+    /// Writes a color, packing it into this view's format.
+    pub fn put(&mut self, x: usize, y: usize, color: Color) {
+        self.set(x, y, self.format.pack(color));
+    }
+
+    /// Returns row `y` of the view as a mutable slice of raw pixels.
+    pub fn row_mut(&mut self, y: usize) -> &mut [u32] {
+        let start = y * self.stride;
+        &mut self.pixels[start..start + self.width]
+    }
+
+    /// Fills the whole view with `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.fill_rect(Rect::new(0, 0, self.width, self.height), color);
+    }
+
+    /// Fills a rectangle (clipped to the view) with `color`.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = rect.clipped(self.width, self.height);
+        let pixel = self.format.pack(color);
+        for y in rect.y..rect.y + rect.height {
+            self.row_mut(y)[rect.x..rect.x + rect.width].fill(pixel);
+        }
+    }
+
+    /// Copies a region of `src` into this view with its top-left corner at (x, y).
+    ///
+    /// The copy is clipped to both buffers, and pixels are converted if the
+    /// formats differ.
+    pub fn blit(&mut self, src: &Framebuffer, src_rect: Rect, x: usize, y: usize) {
+        let src_rect = src_rect.clipped(src.width, src.height);
+        let dst = Rect::new(x, y, src_rect.width, src_rect.height).clipped(self.width, self.height);
+
+        for row in 0..dst.height {
+            let src_start = (src_rect.y + row) * src.stride + src_rect.x;
+            let src_row = &src.pixels[src_start..src_start + dst.width];
+            let format = self.format;
+            let dst_row = &mut self.row_mut(dst.y + row)[dst.x..dst.x + dst.width];
+
+            if src.format == format {
+                dst_row.copy_from_slice(src_row);
+            } else {
+                for (d, &s) in dst_row.iter_mut().zip(src_row) {
+                    *d = src.format.convert(s, format);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_round_trip() {
+        let color = Color::new(0x11, 0x22, 0x33, 0x44);
+        assert_eq!(PixelFormat::Argb32.pack(color), 0x44112233);
+        assert_eq!(
+            PixelFormat::Rgba8.pack(color).to_ne_bytes(),
+            [0x11, 0x22, 0x33, 0x44]
+        );
+        assert_eq!(
+            PixelFormat::Bgra8.pack(color).to_ne_bytes(),
+            [0x33, 0x22, 0x11, 0x44]
+        );
+
+        for format in [PixelFormat::Rgba8, PixelFormat::Bgra8, PixelFormat::Argb32] {
+            assert_eq!(
+                format.unpack(format.pack(color)).to_array(),
+                color.to_array()
+            );
+        }
+    }
+
+    #[test]
+    fn test_viewport_is_offset_and_clipped() {
+        let mut fb = Framebuffer::new(4, 4, PixelFormat::Argb32);
+        let white = Color::new(255, 255, 255, 255);
+
+        let mut view = fb.viewport(Rect::new(2, 1, 10, 10));
+        assert_eq!((view.width(), view.height()), (2, 3));
+        view.fill_rect(Rect::new(0, 0, 100, 1), white);
+
+        assert_eq!(fb.get(1, 1), Some(0));
+        assert_eq!(fb.get(2, 1), Some(0xFFFFFFFF));
+        assert_eq!(fb.get(3, 1), Some(0xFFFFFFFF));
+        assert_eq!(fb.get(2, 2), Some(0));
+    }
+
+    #[test]
+    fn test_blit_converts_formats() {
+        let mut src = Framebuffer::new(2, 2, PixelFormat::Rgba8);
+        src.clear(Color::new(1, 2, 3, 255));
+
+        let mut dst = Framebuffer::with_stride(3, 3, 8, PixelFormat::Argb32);
+        dst.view().blit(&src, Rect::new(0, 0, 2, 2), 2, 2);
+
+        assert_eq!(dst.get(2, 2), Some(0xFF010203));
+        assert_eq!(dst.get(1, 1), Some(0));
+    }
+
+    #[test]
+    fn test_write_rgba8() {
+        let mut fb = Framebuffer::with_stride(2, 1, 4, PixelFormat::Argb32);
+        fb.view().put(1, 0, Color::new(9, 8, 7, 6));
+
+        let mut frame = [0u8; 8];
+        fb.write_rgba8(&mut frame);
+        assert_eq!(frame, [0, 0, 0, 0, 9, 8, 7, 6]);
+
+        // Empty buffers have nothing to copy
+        Framebuffer::new(0, 4, PixelFormat::Argb32).write_rgba8(&mut []);
+        Framebuffer::new(4, 0, PixelFormat::Argb32).write_rgba8(&mut []);
+    }
+}
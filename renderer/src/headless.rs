@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::{
+    framebuffer::{Framebuffer, PixelFormat},
+    map::Map,
+    player::Player,
+    render::{RenderOptions, render_with},
+};
+
+/// Renders a single frame off-screen and returns it as an RGBA image.
+pub fn render_to_image(
+    player: &Player,
+    map: &Map,
+    options: &RenderOptions,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let mut fb = Framebuffer::new(width as usize, height as usize, PixelFormat::Rgba8);
+    render_with(player, map, options, &mut fb.view());
+
+    let mut bytes = vec![0u8; fb.width() * fb.height() * 4];
+    fb.write_rgba8(&mut bytes);
+    RgbaImage::from_raw(width, height, bytes).expect("buffer matches image size")
+}
+
+/// Renders a single frame off-screen and saves it as a PNG file.
+pub fn render_to_png(
+    player: &Player,
+    map: &Map,
+    options: &RenderOptions,
+    width: u32,
+    height: u32,
+    path: impl AsRef<Path>,
+) -> image::ImageResult<()> {
+    let image = render_to_image(player, map, options, width, height);
+    image.save_with_format(path, image::ImageFormat::Png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_to_image() {
+        let map: Map = "#####\n#...#\n#####".parse().unwrap();
+        let mut player = Player::new(1.5, 1.5);
+        player.set_angle(0.0);
+
+        let image = render_to_image(&player, &map, &RenderOptions::default(), 16, 12);
+        assert_eq!(image.dimensions(), (16, 12));
+
+        // Looking down the corridor at the east wall
+        assert_eq!(image.get_pixel(8, 6).0, [0x88, 0x00, 0x00, 0xFF]);
+    }
+}
@@ -0,0 +1,453 @@
+use std::{collections::HashMap, f32::consts::PI, fmt, str::FromStr};
+
+use crate::{
+    color::Color,
+    draw,
+    font::{Align, Font, TextStyle, parse_tagged_line},
+    framebuffer::{FrameView, Rect},
+    texture::Texture,
+};
+
+/// The bar along the bottom of the screen. The 3D view shrinks to make room.
+#[derive(Clone, Copy, Debug)]
+pub struct StatusBarLayout {
+    pub height: u32,
+    /// Fills the parts of the bar the image does not cover.
+    pub color: Color,
+}
+
+/// A number drawn with the HUD's digit font.
+#[derive(Clone, Debug)]
+pub struct CounterLayout {
+    /// The key used with `Hud::set_counter`.
+    pub name: String,
+    /// Position relative to the status bar's top-left corner, or to the
+    /// screen's if there is no status bar.
+    pub x: i32,
+    pub y: i32,
+    /// Values are clamped to what fits in this many digits.
+    pub digits: u32,
+    pub align: Align,
+    pub color: Color,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CrosshairLayout {
+    /// Length of each of the four arms, in pixels.
+    pub size: u32,
+    /// Empty space between the center and the arms.
+    pub gap: u32,
+    pub color: Color,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponLayout {
+    /// How far the weapon sways while walking, in pixels.
+    pub bob: f32,
+    /// Frames per second of the firing animation.
+    pub fps: f32,
+}
+
+/// Which HUD elements exist and where they go.
+///
+/// Parsed from a text description, one element per line:
+///
+/// ```text
+/// # Doom-style HUD
+/// statusbar height=32 color=#404040
+/// counter name=health x=48 y=8 digits=3 align=right
+/// counter name=ammo x=96 y=8 digits=3 align=right color=#FFCC00
+/// crosshair size=3 gap=2 color=#00FF00
+/// weapon bob=6 fps=12
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HudLayout {
+    pub status_bar: Option<StatusBarLayout>,
+    pub counters: Vec<CounterLayout>,
+    pub crosshair: Option<CrosshairLayout>,
+    pub weapon: Option<WeaponLayout>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseLayoutError {
+    /// 1-based line of the offending element.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseLayoutError {}
+
+impl FromStr for HudLayout {
+    type Err = ParseLayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layout = HudLayout::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let error = |message: String| ParseLayoutError {
+                line: index + 1,
+                message,
+            };
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            let Some((tag, attrs)) = parse_tagged_line(line) else {
+                continue;
+            };
+
+            let raw = |key: &str, default: Option<&'static str>| {
+                attrs
+                    .get(key)
+                    .copied()
+                    .or(default)
+                    .ok_or_else(|| error(format!("{tag} is missing {key}")))
+            };
+            let number = |key: &str, default: Option<&'static str>| -> Result<f32, _> {
+                let value = raw(key, default)?;
+                value
+                    .parse()
+                    .map_err(|_| error(format!("invalid {key}: {value}")))
+            };
+            let color = |key: &str, default: &'static str| {
+                let value = raw(key, Some(default))?;
+                value
+                    .strip_prefix('#')
+                    .filter(|hex| hex.len() == 6)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .map(Color::from_hex)
+                    .ok_or_else(|| error(format!("invalid {key}: {value}")))
+            };
+
+            match tag {
+                "statusbar" => {
+                    layout.status_bar = Some(StatusBarLayout {
+                        height: number("height", None)? as u32,
+                        color: color("color", "#000000")?,
+                    })
+                }
+                "counter" => {
+                    let align = match raw("align", Some("left"))? {
+                        "left" => Align::Left,
+                        "center" => Align::Center,
+                        "right" => Align::Right,
+                        other => return Err(error(format!("invalid align: {other}"))),
+                    };
+                    layout.counters.push(CounterLayout {
+                        name: raw("name", None)?.to_string(),
+                        x: number("x", None)? as i32,
+                        y: number("y", None)? as i32,
+                        digits: number("digits", Some("3"))? as u32,
+                        align,
+                        color: color("color", "#FFFFFF")?,
+                    });
+                }
+                "crosshair" => {
+                    layout.crosshair = Some(CrosshairLayout {
+                        size: number("size", Some("3"))? as u32,
+                        gap: number("gap", Some("2"))? as u32,
+                        color: color("color", "#FFFFFF")?,
+                    })
+                }
+                "weapon" => {
+                    layout.weapon = Some(WeaponLayout {
+                        bob: number("bob", Some("6"))?,
+                        fps: number("fps", Some("12"))?,
+                    })
+                }
+                other => return Err(error(format!("unknown element {other:?}"))),
+            }
+        }
+
+        Ok(layout)
+    }
+}
+
+/// The 2D overlay drawn on top of the 3D view.
+///
+/// Render the world into `viewport`, then call `draw` on the whole frame.
+pub struct Hud {
+    pub layout: HudLayout,
+    /// Centered at the bottom of the screen, over the status bar color.
+    pub status_bar: Option<Texture>,
+    /// Used for the counters. The built-in font by default.
+    pub digits: Font,
+    /// Frame 0 is the idle pose, the rest play in order when firing.
+    pub weapon_frames: Vec<Texture>,
+
+    counters: HashMap<String, i32>,
+    weapon_frame: usize,
+    frame_time: f32,
+    bob_phase: f32,
+    /// 0.0 standing still, 1.0 at full speed. Eases towards the current speed.
+    bob_amount: f32,
+}
+
+impl Hud {
+    /// How many full bob cycles (two steps) happen per second at full speed.
+    const BOB_RATE: f32 = 1.0;
+
+    pub fn new(layout: HudLayout) -> Self {
+        Self {
+            layout,
+            status_bar: None,
+            digits: Font::builtin(),
+            weapon_frames: Vec::new(),
+            counters: HashMap::new(),
+            weapon_frame: 0,
+            frame_time: 0.0,
+            bob_phase: 0.0,
+            bob_amount: 0.0,
+        }
+    }
+
+    pub fn set_counter(&mut self, name: &str, value: i32) {
+        self.counters.insert(name.to_string(), value);
+    }
+
+    pub fn counter(&self, name: &str) -> Option<i32> {
+        self.counters.get(name).copied()
+    }
+
+    /// The part of a `width` x `height` frame left for the 3D view.
+    pub fn viewport(&self, width: usize, height: usize) -> Rect {
+        let bar = self.layout.status_bar.map_or(0, |bar| bar.height as usize);
+        Rect::new(0, 0, width, height.saturating_sub(bar))
+    }
+
+    /// Starts the firing animation, unless it is already playing.
+    pub fn fire(&mut self) {
+        if self.weapon_frame == 0 && self.weapon_frames.len() > 1 {
+            self.weapon_frame = 1;
+            self.frame_time = 0.0;
+        }
+    }
+
+    /// The weapon frame currently shown.
+    pub fn weapon_frame(&self) -> usize {
+        self.weapon_frame
+    }
+
+    /// Advances the weapon animation and bob. `speed` is how fast the player
+    /// moves, from 0.0 (standing) to 1.0 (running).
+    pub fn update(&mut self, dt: f32, speed: f32) {
+        let speed = math::clamp(speed, 0.0, 1.0);
+        self.bob_amount += (speed - self.bob_amount) * (dt * 8.0).min(1.0);
+        self.bob_phase = (self.bob_phase + dt * speed * Self::BOB_RATE * 2.0 * PI) % (2.0 * PI);
+
+        if self.weapon_frame == 0 {
+            return;
+        }
+        let fps = self.layout.weapon.map_or(12.0, |w| w.fps).max(f32::EPSILON);
+        self.frame_time += dt;
+        while self.weapon_frame != 0 && self.frame_time >= 1.0 / fps {
+            self.frame_time -= 1.0 / fps;
+            self.weapon_frame = (self.weapon_frame + 1) % self.weapon_frames.len().max(1);
+        }
+    }
+
+    /// The weapon's offset from its rest position: swaying sideways and
+    /// dipping twice per cycle, once for each step.
+    fn bob_offset(&self) -> (i32, i32) {
+        let bob = self.layout.weapon.map_or(0.0, |w| w.bob) * self.bob_amount;
+        let x = self.bob_phase.cos() * bob;
+        let y = self.bob_phase.sin().abs() * bob;
+        (x.round() as i32, y.round() as i32)
+    }
+
+    /// Draws the current weapon frame centered at the bottom of `viewport`.
+    fn draw_weapon(&self, target: &mut FrameView, viewport: Rect) {
+        if let Some(frame) = self.weapon_frames.get(self.weapon_frame)
+            && self.layout.weapon.is_some()
+        {
+            // Relative to the viewport, which the sub-view already starts at
+            let (bob_x, bob_y) = self.bob_offset();
+            let x = (viewport.width as i32 - frame.width() as i32) / 2 + bob_x;
+            let y = viewport.height as i32 - frame.height() as i32 + bob_y;
+            draw::blit(&mut target.sub_view(viewport), frame, x, y);
+        }
+    }
+
+    /// Draws every HUD element into the frame the 3D view was rendered to.
+    pub fn draw(&self, target: &mut FrameView) {
+        let (width, height) = (target.width(), target.height());
+        let viewport = self.viewport(width, height);
+
+        // 1. The weapon sits at the bottom of the 3D view, under everything else
+        self.draw_weapon(target, viewport);
+
+        // 2. The crosshair in the middle of the 3D view
+        if let Some(crosshair) = self.layout.crosshair {
+            let cx = (viewport.x + viewport.width / 2) as i32;
+            let cy = (viewport.y + viewport.height / 2) as i32;
+            let (gap, size) = (crosshair.gap as i32, crosshair.size);
+            let reach = gap + size as i32;
+            let color = crosshair.color;
+            draw::fill_rect(target, cx + 1 + gap, cy, size, 1, color);
+            draw::fill_rect(target, cx - reach, cy, size, 1, color);
+            draw::fill_rect(target, cx, cy + 1 + gap, 1, size, color);
+            draw::fill_rect(target, cx, cy - reach, 1, size, color);
+        }
+
+        // 3. The status bar below the 3D view
+        let origin = match self.layout.status_bar {
+            Some(bar) => {
+                let rect = Rect::new(0, viewport.height, width, bar.height as usize);
+                target.fill_rect(rect, bar.color);
+
+                let image_x = match &self.status_bar {
+                    Some(image) => {
+                        let x = (width as i32 - image.width() as i32) / 2;
+                        draw::blit(&mut target.sub_view(rect), image, x, 0);
+                        x
+                    }
+                    None => 0,
+                };
+                (rect.x as i32 + image_x, rect.y as i32)
+            }
+            None => (0, 0),
+        };
+
+        // 4. The counters, relative to the status bar image
+        for counter in &self.layout.counters {
+            let Some(value) = self.counter(&counter.name) else {
+                continue;
+            };
+            let max = 10i32.saturating_pow(counter.digits).saturating_sub(1);
+            let text = math::clamp(value, 0, max).to_string();
+            let style = TextStyle {
+                align: counter.align,
+                ..TextStyle::new(counter.color)
+            };
+            self.digits.draw(
+                target,
+                origin.0 + counter.x,
+                origin.1 + counter.y,
+                &text,
+                &style,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    const LAYOUT: &str = "\
+# test HUD
+statusbar height=8 color=#202020
+counter name=health x=12 y=1 digits=3 align=right color=#FF0000
+crosshair size=2 gap=1 color=#00FF00
+weapon bob=4 fps=10";
+
+    fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Texture {
+        Texture::new(width, height, rgba.repeat((width * height) as usize))
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        let layout: HudLayout = LAYOUT.parse().unwrap();
+        assert_eq!(layout.status_bar.unwrap().height, 8);
+        assert_eq!(layout.counters.len(), 1);
+        assert_eq!(layout.counters[0].name, "health");
+        assert_eq!(layout.counters[0].align, Align::Right);
+        assert_eq!(layout.counters[0].color.to_array(), [255, 0, 0, 255]);
+        assert_eq!(layout.crosshair.unwrap().gap, 1);
+        assert_eq!(layout.weapon.unwrap().fps, 10.0);
+
+        let err = "crosshair\ncounter x=1 y=2"
+            .parse::<HudLayout>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("name"));
+        assert!("sparkles".parse::<HudLayout>().is_err());
+    }
+
+    #[test]
+    fn test_status_bar_shrinks_viewport() {
+        let hud = Hud::new(LAYOUT.parse().unwrap());
+        assert_eq!(hud.viewport(320, 200), Rect::new(0, 0, 320, 192));
+        assert_eq!(
+            Hud::new(HudLayout::default()).viewport(320, 200),
+            Rect::new(0, 0, 320, 200)
+        );
+    }
+
+    #[test]
+    fn test_draw_bar_counter_and_crosshair() {
+        let mut hud = Hud::new(LAYOUT.parse().unwrap());
+        hud.set_counter("health", 1234);
+
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+        hud.draw(&mut fb.view());
+
+        // The bar covers the bottom 8 rows
+        assert_eq!(fb.get(0, 11), Some(0xFF000000));
+        assert_eq!(fb.get(0, 12), Some(0xFF202020));
+
+        // "999", right aligned at x = 12: the left column of the last '9' at x = 8
+        assert_eq!(fb.get(8, 13), Some(0xFFFF0000));
+        assert_eq!(fb.get(12, 13), Some(0xFF202020));
+
+        // The crosshair arms around the center of the 20x12 view, (10, 6)
+        assert_eq!(fb.get(12, 6), Some(0xFF00FF00));
+        assert_eq!(fb.get(11, 6), Some(0xFF000000));
+        assert_eq!(fb.get(10, 3), Some(0xFF00FF00));
+        assert_eq!(fb.get(10, 6), Some(0xFF000000));
+    }
+
+    #[test]
+    fn test_weapon_animation_and_transparency() {
+        let mut hud = Hud::new(LAYOUT.parse().unwrap());
+        hud.weapon_frames = vec![
+            solid(4, 4, [0, 0, 255, 255]),
+            solid(4, 4, [255, 255, 0, 255]),
+            solid(4, 4, [0, 0, 0, 0]),
+        ];
+
+        hud.fire();
+        assert_eq!(hud.weapon_frame(), 1);
+        hud.update(0.15, 0.0);
+        assert_eq!(hud.weapon_frame(), 2);
+        hud.update(0.1, 0.0);
+        assert_eq!(hud.weapon_frame(), 0);
+
+        // The idle frame is drawn centered at the bottom of the viewport
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+        hud.draw(&mut fb.view());
+        assert_eq!(fb.get(8, 11), Some(0xFF0000FF));
+        assert_eq!(fb.get(8, 7), Some(0xFF000000));
+
+        // A fully transparent frame leaves the view untouched
+        hud.fire();
+        hud.update(0.1, 0.0);
+        fb.clear(Color::new(0, 0, 0, 255));
+        hud.draw(&mut fb.view());
+        assert_eq!(fb.get(8, 11), Some(0xFF000000));
+    }
+
+    #[test]
+    fn test_weapon_stays_inside_an_offset_viewport() {
+        let mut hud = Hud::new(LAYOUT.parse().unwrap());
+        hud.weapon_frames = vec![solid(4, 4, [0, 0, 255, 255])];
+
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        fb.clear(Color::new(0, 0, 0, 255));
+        hud.draw_weapon(&mut fb.view(), Rect::new(4, 2, 12, 10));
+
+        // Centered at the bottom of the 12x10 view starting at (4, 2)
+        assert_eq!(fb.get(8, 8), Some(0xFF0000FF));
+        assert_eq!(fb.get(11, 11), Some(0xFF0000FF));
+        assert_eq!(fb.get(12, 8), Some(0xFF000000));
+        assert_eq!(fb.get(8, 7), Some(0xFF000000));
+    }
+}
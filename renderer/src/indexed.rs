@@ -0,0 +1,148 @@
+use crate::{
+    color::Color,
+    framebuffer::FrameView,
+    palette::{Colormaps, Palette},
+    texture::IndexedTexture,
+};
+
+/// Light levels used by `IndexedMode::new`, as many as Doom's COLORMAP.
+pub const DEFAULT_LEVELS: usize = 32;
+
+/// Everything `render_indexed` needs to draw in palette indices.
+///
+/// The colormaps are built once from the palette and map indices to
+/// indices, so cycling or tinting the palette handed to
+/// `IndexedFrame::present` still affects shaded pixels. Keep `palette`
+/// itself unchanged: the sky is remapped through its `Palette::lookup`
+/// table, which `new` builds up front and any change throws away, moving
+/// the cost of rebuilding it into the next frame.
+#[derive(Clone, Debug)]
+pub struct IndexedMode {
+    pub palette: Palette,
+    pub colormaps: Colormaps,
+    /// Wall textures, `textures[n - 1]` for tile value `n`. Walls without
+    /// one are drawn in the flat color closest to the true-color renderer.
+    pub textures: Vec<IndexedTexture>,
+}
+
+impl IndexedMode {
+    /// Creates a mode with `DEFAULT_LEVELS` colormaps fading to black and no textures.
+    pub fn new(palette: Palette) -> Self {
+        let colormaps = Colormaps::new(&palette, DEFAULT_LEVELS, Color::BLACK);
+        palette.build_lookup();
+        Self {
+            palette,
+            colormaps,
+            textures: Vec::new(),
+        }
+    }
+
+    /// Returns the texture for walls with tile value `wall_value`.
+    pub fn texture(&self, wall_value: u8) -> Option<&IndexedTexture> {
+        let index = (wall_value as usize).checked_sub(1)?;
+        self.textures.get(index)
+    }
+}
+
+/// An 8-bit frame of palette indices, stored column by column like `ColumnBuffer`.
+pub struct IndexedFrame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl IndexedFrame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Resizes the frame, keeping the allocation when it is big enough.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.pixels.resize(width * height, 0);
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[x * self.height + y])
+        } else {
+            None
+        }
+    }
+
+    /// Returns column `x`, `height` indices long from top to bottom.
+    pub fn column_mut(&mut self, x: usize) -> &mut [u8] {
+        &mut self.pixels[x * self.height..(x + 1) * self.height]
+    }
+
+    /// The raw indices, one column after another.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Converts the frame to true color through `palette` and writes it to `target`.
+    ///
+    /// Only the overlapping area is written if the sizes differ.
+    pub fn present(&self, palette: &Palette, target: &mut FrameView) {
+        let packed = palette.packed(target.format());
+        let width = self.width.min(target.width());
+        let height = self.height.min(target.height());
+
+        for y in 0..height {
+            let row = &mut target.row_mut(y)[..width];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = packed[self.pixels[x * self.height + y] as usize];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    #[test]
+    fn test_present_looks_up_the_palette() {
+        let mut frame = IndexedFrame::new(2, 2);
+        frame.column_mut(1).copy_from_slice(&[1, 2]);
+
+        let mut palette = Palette::from_colors(&[
+            Color::BLACK,
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+        ]);
+        let mut fb = Framebuffer::new(2, 2, PixelFormat::Argb32);
+        frame.present(&palette, &mut fb.view());
+        assert_eq!(fb.get(0, 0), Some(0xFF000000));
+        assert_eq!(fb.get(1, 0), Some(0xFFFF0000));
+        assert_eq!(fb.get(1, 1), Some(0xFF00FF00));
+
+        // Cycling the palette recolors the frame without redrawing it
+        palette.rotate(1, 2, 1);
+        frame.present(&palette, &mut fb.view());
+        assert_eq!(fb.get(1, 0), Some(0xFF00FF00));
+    }
+
+    #[test]
+    fn test_texture_by_tile_value() {
+        let mut mode = IndexedMode::new(Palette::default());
+        mode.textures.push(IndexedTexture::new(1, 1, vec![7]));
+        assert!(mode.texture(0).is_none());
+        assert_eq!(mode.texture(1).unwrap().indices(), [7]);
+        assert!(mode.texture(2).is_none());
+    }
+}
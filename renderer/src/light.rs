@@ -0,0 +1,163 @@
+use math::vector::Vector2;
+
+use crate::{color::Color, map::Map, raycaster::Ray};
+
+/// A light that brightens everything around it, fading out at `radius`.
+///
+/// Static lights are baked into the `Map` light grid once with `bake`.
+/// Dynamic lights (muzzle flashes, torches) are passed to the renderer every
+/// frame through `RenderOptions::lights` and are not occluded by walls.
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub pos: Vector2,
+    /// The distance at which the light no longer contributes anything.
+    pub radius: f32,
+    /// The brightness at the center of the light (1.0 = full bright).
+    pub intensity: f32,
+    /// The color of the light. Use white for plain brightness.
+    pub color: Color,
+}
+
+impl PointLight {
+    pub fn new(x: f32, y: f32, radius: f32, intensity: f32) -> Self {
+        Self {
+            pos: Vector2::new(x, y),
+            radius,
+            intensity,
+            color: Color::new(255, 255, 255, 255),
+        }
+    }
+
+    /// Returns the brightness this light adds at `point`, ignoring walls.
+    pub fn falloff(&self, point: &Vector2) -> f32 {
+        if self.radius <= 0.0 {
+            return 0.0;
+        }
+        let dist = point.add(&self.pos.scale(-1.0)).length();
+        let t = (1.0 - dist / self.radius).max(0.0);
+        self.intensity * t * t
+    }
+
+    /// Returns `true` if no wall blocks the straight line from the light to `point`.
+    pub fn reaches(&self, map: &Map, point: &Vector2) -> bool {
+        // With an unnormalized direction, the ray reaches `point` at distance 1.0
+        let dir = point.add(&self.pos.scale(-1.0));
+        if dir.length() < 1e-6 {
+            return true;
+        }
+
+        let mut ray = Ray::new(self.pos, dir);
+        match ray.cast(map) {
+            Some(hit) => {
+                hit.distance >= 1.0 || (hit.map_x, hit.map_y) == (point.x as i32, point.y as i32)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Bakes static lights into the light level of every `Map` cell.
+///
+/// Each cell starts at `ambient` and receives every light that can see its center.
+pub fn bake(map: &mut Map, ambient: Color, lights: &[PointLight]) {
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let center = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let mut level = [ambient.r as f32, ambient.g as f32, ambient.b as f32];
+
+            for light in lights {
+                let amount = light.falloff(&center);
+                if amount <= 0.0 || !light.reaches(map, &center) {
+                    continue;
+                }
+                level[0] += light.color.r as f32 * amount;
+                level[1] += light.color.g as f32 * amount;
+                level[2] += light.color.b as f32 * amount;
+            }
+
+            map.set_light(x, y, to_color(level));
+        }
+    }
+}
+
+/// Returns the light at a world position: the baked level of its cell plus
+/// the contribution of every dynamic light.
+pub fn sample(map: &Map, lights: &[PointLight], point: &Vector2) -> Color {
+    let base = if point.x < 0.0 || point.y < 0.0 {
+        None
+    } else {
+        map.get_light(point.x as u32, point.y as u32)
+    }
+    .unwrap_or(Color::new(0, 0, 0, 255));
+
+    if lights.is_empty() {
+        return base;
+    }
+
+    let mut level = [base.r as f32, base.g as f32, base.b as f32];
+    for light in lights {
+        let amount = light.falloff(point);
+        level[0] += light.color.r as f32 * amount;
+        level[1] += light.color.g as f32 * amount;
+        level[2] += light.color.b as f32 * amount;
+    }
+    to_color(level)
+}
+
+/// Multiplies a surface color by a light level (255 = unchanged).
+///
+/// The same as `color.multiply(&light)`.
+pub fn modulate(color: Color, light: Color) -> Color {
+    color.multiply(&light)
+}
+
+fn to_color(level: [f32; 3]) -> Color {
+    let channel = |v: f32| math::clamp(v.round(), 0.0, 255.0) as u8;
+    Color::new(channel(level[0]), channel(level[1]), channel(level[2]), 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_falloff() {
+        let light = PointLight::new(0.0, 0.0, 4.0, 1.0);
+        assert_eq!(light.falloff(&Vector2::new(0.0, 0.0)), 1.0);
+        assert_eq!(light.falloff(&Vector2::new(2.0, 0.0)), 0.25);
+        assert_eq!(light.falloff(&Vector2::new(5.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_bake_respects_walls() {
+        let mut map = Map::new(5, 1);
+        map.set_tile(2, 0, 1);
+        let torch = PointLight::new(0.5, 0.5, 10.0, 1.0);
+
+        bake(&mut map, Color::new(16, 16, 16, 255), &[torch]);
+
+        // The lit side is bright, the cell behind the wall only gets ambient
+        assert!(map.get_light(1, 0).unwrap().r > 200);
+        assert_eq!(map.get_light(3, 0).unwrap().to_array(), [16, 16, 16, 255]);
+    }
+
+    #[test]
+    fn test_sample_adds_colored_dynamic_lights() {
+        let mut map = Map::new(2, 2);
+        bake(&mut map, Color::new(0, 0, 0, 255), &[]);
+
+        let mut flash = PointLight::new(1.0, 1.0, 2.0, 1.0);
+        flash.color = Color::new(255, 128, 0, 255);
+
+        let lit = sample(&map, &[flash], &Vector2::new(1.0, 1.0));
+        assert_eq!(lit.to_array(), [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn test_modulate() {
+        let white = Color::new(255, 255, 255, 255);
+        let half = Color::new(128, 128, 128, 255);
+        assert_eq!(modulate(white, half).to_array(), [128, 128, 128, 255]);
+        assert_eq!(modulate(half, white).to_array(), [128, 128, 128, 255]);
+    }
+}
@@ -1,7 +1,52 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    color::Color,
+    pushwall::{PushWall, PushWallState},
+};
+
+/// An error produced when parsing a text map fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMapError {
+    /// 1-based line of the offending character.
+    pub line: usize,
+    /// 1-based column of the offending character.
+    pub column: usize,
+    pub found: char,
+}
+
+impl fmt::Display for ParseMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unexpected character {:?} at line {}, column {}",
+            self.found, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseMapError {}
+
+#[derive(Clone, Debug)]
 pub struct Map {
     pub width: u32,
     pub height: u32,
     pub grid: Vec<u8>,
+    /// The height of the wall standing on each tile, in wall units (1.0 = one full wall).
+    pub heights: Vec<f32>,
+    /// The elevation of the floor of each tile. Walls stand on top of it.
+    pub floor_heights: Vec<f32>,
+    /// The light level of each tile (255 = full bright). See `light::bake`.
+    pub light: Vec<Color>,
+    /// Whether each tile has a roof over it. The sky only shows above open tiles.
+    pub covered: Vec<bool>,
+    /// Secret push-walls. Start them with `push` and move them with `update`,
+    /// which keep the per-tile lookup behind `pushwall_at` in sync.
+    pub pushwalls: Vec<PushWall>,
+    /// Cached `max_column_top`, raised by the setters.
+    max_top: f32,
+    /// The index of the moving push-wall overlapping each tile, if any.
+    pushwall_cells: Vec<Option<usize>>,
 }
 
 impl Map {
@@ -11,6 +56,13 @@ impl Map {
             height,
             // Initialize the grid with `0` (empty floor) for every tile
             grid: vec![0; (width * height) as usize],
+            heights: vec![1.0; (width * height) as usize],
+            floor_heights: vec![0.0; (width * height) as usize],
+            light: vec![Color::new(255, 255, 255, 255); (width * height) as usize],
+            covered: vec![false; (width * height) as usize],
+            pushwalls: Vec::new(),
+            max_top: 1.0,
+            pushwall_cells: vec![None; (width * height) as usize],
         }
     }
 
@@ -19,6 +71,7 @@ impl Map {
         if x < self.width && y < self.height {
             let idx = (y * self.width + x) as usize;
             self.grid[idx] = value;
+            self.raise_max_top(x, y);
         }
     }
 
@@ -29,6 +82,259 @@ impl Map {
         let idx = (y * self.width + x) as usize;
         self.grid.get(idx).copied()
     }
+
+    /// Sets the height of the wall on tile (x, y).
+    pub fn set_wall_height(&mut self, x: u32, y: u32, height: f32) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.heights[idx] = height;
+            self.raise_max_top(x, y);
+        }
+    }
+
+    pub fn get_wall_height(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.heights.get((y * self.width + x) as usize).copied()
+    }
+
+    /// Sets the floor elevation of tile (x, y). Empty tiles with a raised floor
+    /// act as platforms.
+    pub fn set_floor_height(&mut self, x: u32, y: u32, height: f32) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.floor_heights[idx] = height;
+            self.raise_max_top(x, y);
+        }
+    }
+
+    pub fn get_floor_height(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.floor_heights
+            .get((y * self.width + x) as usize)
+            .copied()
+    }
+
+    pub fn set_light(&mut self, x: u32, y: u32, light: Color) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.light[idx] = light;
+        }
+    }
+
+    pub fn get_light(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.light.get((y * self.width + x) as usize).copied()
+    }
+
+    /// Puts a roof over tile (x, y), hiding the sky above it.
+    pub fn set_covered(&mut self, x: u32, y: u32, covered: bool) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            self.covered[idx] = covered;
+        }
+    }
+
+    /// Returns `true` if the sky is visible above tile (x, y).
+    pub fn is_open_sky(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        !self.covered[(y * self.width + x) as usize]
+    }
+
+    /// Returns the elevation of the top of the solid column on tile (x, y).
+    ///
+    /// For walls this is the floor height plus the wall height. For empty
+    /// tiles it is just the floor height (0.0 for plain floor).
+    pub fn column_top(&self, x: u32, y: u32) -> Option<f32> {
+        let tile = self.get_tile(x, y)?;
+        let floor = self.get_floor_height(x, y)?;
+        if tile > 0 {
+            Some(floor + self.get_wall_height(x, y)?)
+        } else {
+            Some(floor)
+        }
+    }
+
+    /// Returns the tallest column top in the whole map, at least 1.0.
+    ///
+    /// Once a ray hits a column this tall, nothing behind it can be visible.
+    /// The value is cached and only ever raised by the setters, so after
+    /// lowering columns it may be higher than needed, which is still safe.
+    /// Call `recompute_max_column_top` after writing `grid`, `heights` or
+    /// `floor_heights` directly.
+    pub fn max_column_top(&self) -> f32 {
+        self.max_top
+    }
+
+    /// Rescans every tile for `max_column_top`.
+    pub fn recompute_max_column_top(&mut self) {
+        self.max_top = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| self.column_top(x, y))
+            .fold(1.0, f32::max);
+    }
+
+    fn raise_max_top(&mut self, x: u32, y: u32) {
+        if let Some(top) = self.column_top(x, y) {
+            self.max_top = self.max_top.max(top);
+        }
+    }
+
+    /// Marks the wall at (x, y) as a secret push-wall that slides `cells` cells.
+    ///
+    /// Returns `false` if there is no wall at that position.
+    pub fn add_pushwall(&mut self, x: u32, y: u32, cells: u32) -> bool {
+        match self.get_tile(x, y) {
+            Some(tile) if tile > 0 => {
+                self.pushwalls.push(PushWall::new(tile, x, y, cells));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pushes the idle push-wall at (x, y) in the given direction, which
+    /// must be one of the four unit steps along the grid axes.
+    ///
+    /// The travel distance is shortened if the block would run into another
+    /// wall or off the map. Returns `true` if the block started moving.
+    pub fn push(&mut self, x: u32, y: u32, dir_x: i32, dir_y: i32) -> bool {
+        if dir_x.abs() + dir_y.abs() != 1 {
+            return false;
+        }
+        let Some(idx) = self
+            .pushwalls
+            .iter()
+            .position(|w| w.origin_x == x && w.origin_y == y && w.state == PushWallState::Idle)
+        else {
+            return false;
+        };
+
+        // Count how many free cells there are in front of the block
+        let wanted = self.pushwalls[idx].cells;
+        let mut free = 0;
+        while free < wanted {
+            let nx = x as i32 + dir_x * (free as i32 + 1);
+            let ny = y as i32 + dir_y * (free as i32 + 1);
+            if nx < 0 || ny < 0 || self.get_tile(nx as u32, ny as u32) != Some(0) {
+                break;
+            }
+            free += 1;
+        }
+
+        if free == 0 {
+            return false;
+        }
+
+        let wall = &mut self.pushwalls[idx];
+        wall.dir_x = dir_x;
+        wall.dir_y = dir_y;
+        wall.cells = free;
+        wall.state = PushWallState::Moving;
+        if let Some(cell) = self.cell_index(x as i32, y as i32) {
+            self.pushwall_cells[cell] = Some(idx);
+        }
+        true
+    }
+
+    /// Advances every moving push-wall and keeps the grid occupancy in sync.
+    ///
+    /// Every cell a sliding block overlaps stays solid, and a cell is only
+    /// freed once the block has left it completely.
+    pub fn update(&mut self, dt: f32) {
+        for i in 0..self.pushwalls.len() {
+            if self.pushwalls[i].state != PushWallState::Moving {
+                continue;
+            }
+
+            let before = self.pushwalls[i].occupied_cells();
+            self.pushwalls[i].update(dt);
+            let wall = &self.pushwalls[i];
+            let after = wall.occupied_cells();
+            let (tile, moving) = (wall.tile, wall.state == PushWallState::Moving);
+
+            for (x, y) in before {
+                if let Some(cell) = self.cell_index(x, y) {
+                    self.pushwall_cells[cell] = None;
+                    if !after.contains(&(x, y)) {
+                        self.set_tile(x as u32, y as u32, 0);
+                    }
+                }
+            }
+            for (x, y) in after {
+                if let Some(cell) = self.cell_index(x, y) {
+                    self.set_tile(x as u32, y as u32, tile);
+                    if moving {
+                        self.pushwall_cells[cell] = Some(i);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the moving push-wall that overlaps the given cell, if any.
+    pub fn pushwall_at(&self, x: i32, y: i32) -> Option<&PushWall> {
+        let index = self.pushwall_cells[self.cell_index(x, y)?]?;
+        self.pushwalls.get(index).filter(|w| w.covers(x, y))
+    }
+
+    /// Returns the index of tile (x, y) in the per-tile vectors.
+    fn cell_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some((y as u32 * self.width + x as u32) as usize)
+    }
+}
+
+/// Parses a map from text, one line per row.
+///
+/// `0`-`9` set the tile value, `#` is wall `1`, and `.` or a space is empty
+/// floor. Short lines are padded with floor, and blank lines are ignored.
+impl FromStr for Map {
+    type Err = ParseMapError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
+
+        let width = rows
+            .iter()
+            .map(|(_, l)| l.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut map = Map::new(width as u32, rows.len() as u32);
+
+        for (y, (line_idx, line)) in rows.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                let tile = match ch {
+                    '0'..='9' => ch as u8 - b'0',
+                    '#' => 1,
+                    '.' | ' ' => 0,
+                    _ => {
+                        return Err(ParseMapError {
+                            line: line_idx + 1,
+                            column: x + 1,
+                            found: ch,
+                        });
+                    }
+                };
+                map.set_tile(x as u32, y as u32, tile);
+            }
+        }
+
+        Ok(map)
+    }
 }
 
 #[cfg(test)]
@@ -56,4 +362,94 @@ mod tests {
         assert_eq!(map.get_tile(2, 0), None);
         assert_eq!(map.get_tile(0, 2), None);
     }
+
+    #[test]
+    fn test_parse() {
+        let map: Map = "###\n#.2\n\n#\n".parse().unwrap();
+        assert_eq!((map.width, map.height), (3, 3));
+        assert_eq!(map.get_tile(0, 0), Some(1));
+        assert_eq!(map.get_tile(1, 1), Some(0));
+        assert_eq!(map.get_tile(2, 1), Some(2));
+        assert_eq!(map.get_tile(2, 2), Some(0));
+
+        let err = "##\n#x".parse::<Map>().unwrap_err();
+        assert_eq!((err.line, err.column, err.found), (2, 2, 'x'));
+    }
+
+    #[test]
+    fn test_column_top() {
+        let mut map = Map::new(3, 1);
+        map.set_tile(0, 0, 1);
+        map.set_wall_height(0, 0, 2.0);
+        map.set_floor_height(0, 0, 0.5);
+        map.set_floor_height(1, 0, 0.25);
+
+        assert_eq!(map.column_top(0, 0), Some(2.5));
+        assert_eq!(map.column_top(1, 0), Some(0.25));
+        assert_eq!(map.column_top(2, 0), Some(0.0));
+        assert_eq!(map.max_column_top(), 2.5);
+
+        // Lowering a column keeps the cached value until it is recomputed
+        map.set_wall_height(0, 0, 0.5);
+        assert_eq!(map.max_column_top(), 2.5);
+        map.recompute_max_column_top();
+        assert_eq!(map.max_column_top(), 1.0);
+
+        map.heights[0] = 4.0;
+        map.recompute_max_column_top();
+        assert_eq!(map.max_column_top(), 4.5);
+    }
+
+    #[test]
+    fn test_pushwall_slides_and_settles() {
+        let mut map = Map::new(5, 1);
+        map.set_tile(1, 0, 7);
+        assert!(map.add_pushwall(1, 0, 2));
+        assert!(map.push(1, 0, 1, 0));
+
+        // Halfway between two cells, both stay solid
+        map.update(0.5);
+        assert_eq!(map.get_tile(1, 0), Some(7));
+        assert_eq!(map.get_tile(2, 0), Some(7));
+        assert!(map.pushwall_at(1, 0).is_some());
+        assert!(map.pushwall_at(2, 0).is_some());
+        assert!(map.pushwall_at(3, 0).is_none());
+
+        // Once it has left a cell completely, the cell is free
+        map.update(0.75);
+        assert_eq!(map.get_tile(1, 0), Some(0));
+        assert_eq!(map.get_tile(2, 0), Some(7));
+        assert_eq!(map.get_tile(3, 0), Some(7));
+        assert!(map.pushwall_at(1, 0).is_none());
+
+        map.update(2.0);
+        assert_eq!(map.get_tile(2, 0), Some(0));
+        assert_eq!(map.get_tile(3, 0), Some(7));
+        assert_eq!(map.pushwalls[0].state, PushWallState::Settled);
+        assert!(map.pushwall_at(3, 0).is_none());
+
+        // A settled wall cannot be pushed again
+        assert!(!map.push(1, 0, 1, 0));
+    }
+
+    #[test]
+    fn test_pushwall_only_moves_along_the_grid() {
+        let mut map = Map::new(5, 5);
+        map.set_tile(1, 1, 7);
+        map.add_pushwall(1, 1, 2);
+        assert!(!map.push(1, 1, 1, 1));
+        assert!(!map.push(1, 1, 2, 0));
+        assert!(!map.push(1, 1, 0, 0));
+        assert_eq!(map.pushwalls[0].state, PushWallState::Idle);
+        assert!(map.push(1, 1, 0, 1));
+    }
+
+    #[test]
+    fn test_pushwall_blocked_by_wall() {
+        let mut map = Map::new(4, 1);
+        map.set_tile(1, 0, 7);
+        map.set_tile(2, 0, 1);
+        map.add_pushwall(1, 0, 2);
+        assert!(!map.push(1, 0, 1, 0));
+    }
 }
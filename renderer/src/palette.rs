@@ -1,6 +1,9 @@
-use crate::color::Color;
+use std::{fmt, fs, io, path::Path, sync::OnceLock};
+
+use crate::{color::Color, framebuffer::PixelFormat};
 
 /// A collection of predefined colors used throughout the engine.
+#[allow(clippy::module_inception)]
 pub mod palette {
     use super::Color;
 
@@ -14,3 +17,641 @@ pub mod palette {
     pub const WALL_BLUE: Color = Color::from_hex(0x2980B9);
     pub const WALL_STONE: Color = Color::from_hex(0x7F8C8D);
 }
+
+/// Number of entries in a `Palette`.
+pub const PALETTE_SIZE: usize = 256;
+
+/// Size of one palette as raw RGB bytes, and of each palette in a PLAYPAL lump.
+pub const RAW_SIZE: usize = PALETTE_SIZE * 3;
+
+/// Bits kept per channel by `Palette::lookup`. 5 bits gives a 32 KiB table.
+const LOOKUP_BITS: u32 = 5;
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(io::Error),
+    /// A malformed text palette. `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// Binary palette data that is not a whole number of 768-byte palettes.
+    Size(usize),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(e) => write!(f, "cannot read palette: {e}"),
+            PaletteError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            PaletteError::Size(len) => {
+                write!(f, "expected a multiple of {RAW_SIZE} bytes, got {len}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PaletteError::Io(e) => Some(e),
+            PaletteError::Parse { .. } | PaletteError::Size(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for PaletteError {
+    fn from(e: io::Error) -> Self {
+        PaletteError::Io(e)
+    }
+}
+
+/// 256 colors addressed by an 8-bit index, like a VGA palette.
+///
+/// The indexed renderer draws palette indices, so changing an entry here
+/// recolors every pixel that uses it the next time the frame is presented.
+/// That is how palette cycling (water, lava) and screen flashes work.
+#[derive(Clone)]
+pub struct Palette {
+    colors: [Color; PALETTE_SIZE],
+    /// Built by the first `lookup`, dropped whenever an entry changes.
+    lookup: OnceLock<Box<[u8]>>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new([Color::BLACK; PALETTE_SIZE])
+    }
+}
+
+impl PartialEq for Palette {
+    fn eq(&self, other: &Self) -> bool {
+        self.colors == other.colors
+    }
+}
+
+impl Eq for Palette {}
+
+impl fmt::Debug for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Palette")
+            .field("colors", &self.colors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Palette {
+    pub fn new(colors: [Color; PALETTE_SIZE]) -> Self {
+        Self {
+            colors,
+            lookup: OnceLock::new(),
+        }
+    }
+
+    /// Builds a palette from up to 256 colors. Missing entries are black.
+    pub fn from_colors(colors: &[Color]) -> Self {
+        let mut palette = Self::default();
+        for (entry, &color) in palette.colors.iter_mut().zip(colors) {
+            *entry = color;
+        }
+        palette
+    }
+
+    pub fn colors(&self) -> &[Color; PALETTE_SIZE] {
+        &self.colors
+    }
+
+    pub fn get(&self, index: u8) -> Color {
+        self.colors[index as usize]
+    }
+
+    pub fn set(&mut self, index: u8, color: Color) {
+        self.colors[index as usize] = color;
+        self.lookup = OnceLock::new();
+    }
+
+    /// Returns the index of the entry closest to `color` in RGB space.
+    ///
+    /// Alpha is ignored. On a tie the lowest index wins.
+    pub fn nearest(&self, color: Color) -> u8 {
+        let distance = |c: &Color| {
+            let dr = c.r as i32 - color.r as i32;
+            let dg = c.g as i32 - color.g as i32;
+            let db = c.b as i32 - color.b as i32;
+            dr * dr + dg * dg + db * db
+        };
+
+        let mut best = 0;
+        let mut best_distance = i32::MAX;
+        for (i, c) in self.colors.iter().enumerate() {
+            let d = distance(c);
+            if d < best_distance {
+                best = i;
+                best_distance = d;
+                if d == 0 {
+                    break;
+                }
+            }
+        }
+        best as u8
+    }
+
+    /// Returns the index closest to `color` through a cached lookup table.
+    ///
+    /// Much faster than `nearest` for many colors, but only looks at the top
+    /// 5 bits of each channel, so very similar colors may land on a
+    /// neighbouring entry. The table is built on first use or by
+    /// `build_lookup` (about 32K `nearest` calls) and rebuilt after the
+    /// palette changes, so cycle a copy of the palette rather than one used
+    /// for lookups.
+    pub fn lookup(&self, color: Color) -> u8 {
+        let table = self.lookup_table();
+        let shift = 8 - LOOKUP_BITS;
+        let (r, g, b) = (
+            (color.r >> shift) as usize,
+            (color.g >> shift) as usize,
+            (color.b >> shift) as usize,
+        );
+        table[(r << (2 * LOOKUP_BITS)) | (g << LOOKUP_BITS) | b]
+    }
+
+    /// Builds the `lookup` table now instead of on first use, to keep the
+    /// cost out of a frame.
+    pub fn build_lookup(&self) {
+        self.lookup_table();
+    }
+
+    fn lookup_table(&self) -> &[u8] {
+        self.lookup.get_or_init(|| {
+            let levels = 1 << LOOKUP_BITS;
+            let shift = 8 - LOOKUP_BITS;
+            // Sample the middle of each bucket
+            let center = |i: u32| ((i << shift) | (1 << (shift - 1))) as u8;
+            let mut table = Vec::with_capacity(levels * levels * levels);
+            for r in 0..levels as u32 {
+                for g in 0..levels as u32 {
+                    for b in 0..levels as u32 {
+                        let color = Color::new(center(r), center(g), center(b), 255);
+                        table.push(self.nearest(color));
+                    }
+                }
+            }
+            table.into_boxed_slice()
+        })
+    }
+
+    /// Rotates the entries `first..=last` by `steps` places.
+    ///
+    /// Positive steps move every color one index up, with `last` wrapping
+    /// around to `first`. Does nothing if `last < first`.
+    pub fn rotate(&mut self, first: u8, last: u8, steps: i32) {
+        if last < first {
+            return;
+        }
+        let range = &mut self.colors[first as usize..=last as usize];
+        let steps = steps.rem_euclid(range.len() as i32) as usize;
+        range.rotate_right(steps);
+        self.lookup = OnceLock::new();
+    }
+
+    /// Returns a copy with every entry blended `amount` of the way towards
+    /// `color`, like Doom's damage and pickup palettes.
+    pub fn tinted(&self, color: Color, amount: f32) -> Self {
+        let amount = math::clamp(amount, 0.0, 1.0);
+        Self::new(self.colors.map(|entry| entry.lerp(&color, amount)))
+    }
+
+    /// Packs every entry as `format`, for converting a whole frame of indices.
+    pub fn packed(&self, format: PixelFormat) -> [u32; PALETTE_SIZE] {
+        self.colors.map(|color| format.pack(color))
+    }
+
+    /// Reads 768 bytes of RGB triplets, the format of VGA `.pal` and `.lmp` files.
+    pub fn from_raw(bytes: &[u8]) -> Result<Self, PaletteError> {
+        if bytes.len() != RAW_SIZE {
+            return Err(PaletteError::Size(bytes.len()));
+        }
+        let colors: Vec<Color> = bytes
+            .chunks_exact(3)
+            .map(|rgb| Color::new(rgb[0], rgb[1], rgb[2], 255))
+            .collect();
+        Ok(Self::from_colors(&colors))
+    }
+
+    /// Returns the palette as 768 bytes of RGB triplets.
+    pub fn to_raw(&self) -> Vec<u8> {
+        self.colors.iter().flat_map(|c| [c.r, c.g, c.b]).collect()
+    }
+
+    /// Reads a Doom PLAYPAL lump: consecutive raw palettes, normally 14 of them
+    /// (the normal palette, then the damage, pickup and radiation suit tints).
+    pub fn from_playpal(bytes: &[u8]) -> Result<Vec<Self>, PaletteError> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(RAW_SIZE) {
+            return Err(PaletteError::Size(bytes.len()));
+        }
+        bytes.chunks_exact(RAW_SIZE).map(Self::from_raw).collect()
+    }
+
+    /// Writes `palettes` back to back as a PLAYPAL lump.
+    pub fn to_playpal(palettes: &[Self]) -> Vec<u8> {
+        palettes.iter().flat_map(Self::to_raw).collect()
+    }
+
+    /// Parses a JASC-PAL (Paint Shop Pro) palette. Missing entries are black.
+    pub fn parse_jasc(text: &str) -> Result<Self, PaletteError> {
+        let mut lines = text.lines().enumerate();
+        let mut header = |number: usize, expected: &str| {
+            let line = lines.next().map_or("", |(_, line)| line);
+            if line.trim() == expected {
+                Ok(())
+            } else {
+                Err(PaletteError::Parse {
+                    line: number,
+                    message: format!("expected `{expected}`"),
+                })
+            }
+        };
+        header(1, "JASC-PAL")?;
+        header(2, "0100")?;
+
+        let (i, count) = lines.next().unwrap_or((2, ""));
+        let count: usize = count.trim().parse().map_err(|_| PaletteError::Parse {
+            line: i + 1,
+            message: format!("invalid color count `{}`", count.trim()),
+        })?;
+        if count > PALETTE_SIZE {
+            return Err(PaletteError::Parse {
+                line: i + 1,
+                message: format!("{count} colors do not fit in {PALETTE_SIZE}"),
+            });
+        }
+
+        let mut colors = Vec::with_capacity(count);
+        for (i, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+            if colors.len() == count {
+                break;
+            }
+            colors.push(parse_rgb(line, i + 1)?);
+        }
+        if colors.len() < count {
+            return Err(PaletteError::Parse {
+                line: text.lines().count(),
+                message: format!("expected {count} colors, found {}", colors.len()),
+            });
+        }
+        Ok(Self::from_colors(&colors))
+    }
+
+    /// Formats the palette as JASC-PAL, always with all 256 entries.
+    pub fn to_jasc(&self) -> String {
+        let mut text = format!("JASC-PAL\r\n0100\r\n{PALETTE_SIZE}\r\n");
+        for c in &self.colors {
+            text.push_str(&format!("{} {} {}\r\n", c.r, c.g, c.b));
+        }
+        text
+    }
+
+    /// Parses a GIMP `.gpl` palette. Color names are ignored and missing
+    /// entries are black.
+    pub fn parse_gpl(text: &str) -> Result<Self, PaletteError> {
+        let mut colors = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if i == 0 {
+                if line != "GIMP Palette" {
+                    return Err(PaletteError::Parse {
+                        line: 1,
+                        message: "expected `GIMP Palette`".to_string(),
+                    });
+                }
+                continue;
+            }
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("Name:")
+                || line.starts_with("Columns:")
+            {
+                continue;
+            }
+            if colors.len() == PALETTE_SIZE {
+                return Err(PaletteError::Parse {
+                    line: i + 1,
+                    message: format!("more than {PALETTE_SIZE} colors"),
+                });
+            }
+            colors.push(parse_rgb(line, i + 1)?);
+        }
+        Ok(Self::from_colors(&colors))
+    }
+
+    /// Formats the palette as a GIMP `.gpl` file called `name`.
+    pub fn to_gpl(&self, name: &str) -> String {
+        let mut text = format!("GIMP Palette\nName: {name}\nColumns: 16\n#\n");
+        for (i, c) in self.colors.iter().enumerate() {
+            text.push_str(&format!("{:3} {:3} {:3}\tIndex {i}\n", c.r, c.g, c.b));
+        }
+        text
+    }
+
+    /// Loads a JASC-PAL, GIMP or raw palette, telling them apart by content.
+    ///
+    /// Raw files may hold several palettes, like a PLAYPAL lump; only the
+    /// first is returned. Use `load_playpal` to get all of them.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteError> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(b"JASC-PAL") {
+            Self::parse_jasc(&String::from_utf8_lossy(&bytes))
+        } else if bytes.starts_with(b"GIMP Palette") {
+            Self::parse_gpl(&String::from_utf8_lossy(&bytes))
+        } else {
+            let first = Self::from_playpal(&bytes)?.swap_remove(0);
+            Ok(first)
+        }
+    }
+
+    /// Loads every palette of a Doom PLAYPAL lump.
+    pub fn load_playpal(path: impl AsRef<Path>) -> Result<Vec<Self>, PaletteError> {
+        Self::from_playpal(&fs::read(path)?)
+    }
+
+    /// Saves the palette in the format matching the extension of `path`:
+    /// `.pal` for JASC-PAL, `.gpl` for GIMP, anything else as raw bytes.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PaletteError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("pal") => fs::write(path, self.to_jasc())?,
+            Some("gpl") => {
+                let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                fs::write(path, self.to_gpl(name))?
+            }
+            _ => fs::write(path, self.to_raw())?,
+        }
+        Ok(())
+    }
+}
+
+/// Parses the leading `r g b` of a text palette line. Anything after is ignored.
+fn parse_rgb(line: &str, line_number: usize) -> Result<Color, PaletteError> {
+    let mut channels = line.split_whitespace().map(str::parse::<u8>);
+    match (channels.next(), channels.next(), channels.next()) {
+        (Some(Ok(r)), Some(Ok(g)), Some(Ok(b))) => Ok(Color::new(r, g, b, 255)),
+        _ => Err(PaletteError::Parse {
+            line: line_number,
+            message: format!("expected `r g b`, found `{}`", line.trim()),
+        }),
+    }
+}
+
+/// Light-level lookup tables for a `Palette`.
+///
+/// Level 0 maps every index to itself (full brightness); each following
+/// level maps it to the palette entry closest to the color darkened a bit
+/// further towards `fade`. Shading an indexed pixel is then a single table
+/// lookup, and the result is still a palette index.
+#[derive(Clone, Debug)]
+pub struct Colormaps {
+    levels: usize,
+    maps: Vec<u8>,
+}
+
+impl Colormaps {
+    /// Builds `levels` colormaps (at least 1) fading from the palette to `fade`.
+    pub fn new(palette: &Palette, levels: usize, fade: Color) -> Self {
+        let levels = levels.max(1);
+        let mut maps = Vec::with_capacity(levels * PALETTE_SIZE);
+        for level in 0..levels {
+            let t = if levels > 1 {
+                level as f32 / (levels - 1) as f32
+            } else {
+                0.0
+            };
+            for (i, color) in palette.colors.iter().enumerate() {
+                if level == 0 {
+                    maps.push(i as u8);
+                } else {
+                    maps.push(palette.nearest(color.lerp(&fade, t)));
+                }
+            }
+        }
+        Self { levels, maps }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Returns the 256-entry table for `level`, clamped to the darkest one.
+    pub fn map(&self, level: usize) -> &[u8] {
+        let level = level.min(self.levels - 1);
+        &self.maps[level * PALETTE_SIZE..(level + 1) * PALETTE_SIZE]
+    }
+
+    /// Returns the level for a brightness from 0.0 (fully faded) to 1.0 (full).
+    pub fn level(&self, brightness: f32) -> usize {
+        let brightness = math::clamp(brightness, 0.0, 1.0);
+        ((1.0 - brightness) * (self.levels - 1) as f32).round() as usize
+    }
+
+    /// Returns `index` as seen at `brightness`.
+    pub fn shade(&self, index: u8, brightness: f32) -> u8 {
+        self.map(self.level(brightness))[index as usize]
+    }
+}
+
+/// Animates a range of palette entries by rotating them at a steady rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaletteCycle {
+    pub first: u8,
+    pub last: u8,
+    /// Steps per second. Negative rates cycle the other way.
+    pub rate: f32,
+    elapsed: f32,
+}
+
+impl PaletteCycle {
+    pub fn new(first: u8, last: u8, rate: f32) -> Self {
+        Self {
+            first,
+            last,
+            rate,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the cycle by `dt` seconds, rotating `palette` by the whole
+    /// steps that have passed since the last call.
+    pub fn update(&mut self, dt: f32, palette: &mut Palette) {
+        self.elapsed += dt * self.rate;
+        let steps = self.elapsed.trunc();
+        if steps != 0.0 {
+            self.elapsed -= steps;
+            palette.rotate(self.first, self.last, steps as i32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grays() -> Palette {
+        let mut colors = [Color::BLACK; PALETTE_SIZE];
+        for (i, color) in colors.iter_mut().enumerate() {
+            *color = Color::new(i as u8, i as u8, i as u8, 255);
+        }
+        Palette::new(colors)
+    }
+
+    #[test]
+    fn test_nearest() {
+        let palette = Palette::from_colors(&[
+            Color::BLACK,
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ]);
+        assert_eq!(palette.nearest(Color::new(200, 30, 20, 255)), 1);
+        assert_eq!(palette.nearest(Color::new(10, 20, 180, 255)), 2);
+        // The unused entries are black too, but the first one wins
+        assert_eq!(palette.nearest(Color::new(5, 5, 5, 255)), 0);
+    }
+
+    #[test]
+    fn test_rotate_only_touches_the_range() {
+        let mut palette = grays();
+        palette.rotate(10, 13, 1);
+        assert_eq!(palette.get(9).r, 9);
+        assert_eq!(palette.get(10).r, 13);
+        assert_eq!(palette.get(11).r, 10);
+        assert_eq!(palette.get(13).r, 12);
+        assert_eq!(palette.get(14).r, 14);
+
+        palette.rotate(10, 13, -1);
+        assert_eq!(palette, grays());
+    }
+
+    #[test]
+    fn test_colormaps_fade_to_black() {
+        let palette = grays();
+        let colormaps = Colormaps::new(&palette, 5, Color::BLACK);
+
+        assert_eq!(colormaps.shade(200, 1.0), 200);
+        assert_eq!(colormaps.shade(200, 0.5), 100);
+        assert_eq!(colormaps.shade(200, 0.0), 0);
+        // Out of range brightness and levels are clamped
+        assert_eq!(colormaps.shade(200, 2.0), 200);
+        assert_eq!(colormaps.map(99), colormaps.map(4));
+    }
+
+    #[test]
+    fn test_cycle_waits_for_whole_steps() {
+        let mut palette = grays();
+        let mut cycle = PaletteCycle::new(0, 3, 4.0);
+
+        cycle.update(0.125, &mut palette);
+        assert_eq!(palette.get(0).r, 0);
+        cycle.update(0.125, &mut palette);
+        assert_eq!(palette.get(0).r, 3);
+        assert_eq!(palette.get(1).r, 0);
+    }
+
+    #[test]
+    fn test_tinted() {
+        let palette = grays().tinted(Color::new(255, 0, 0, 255), 1.0);
+        assert!(palette.colors().iter().all(|c| c.r == 255 && c.g == 0));
+    }
+
+    #[test]
+    fn test_lookup_matches_nearest_for_distinct_colors() {
+        let palette = Palette::from_colors(&[
+            Color::BLACK,
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+            Color::WHITE,
+        ]);
+        for color in [
+            Color::new(240, 20, 10, 255),
+            Color::new(10, 200, 40, 255),
+            Color::new(30, 30, 220, 255),
+            Color::new(230, 230, 230, 255),
+            Color::new(12, 12, 12, 255),
+        ] {
+            assert_eq!(palette.lookup(color), palette.nearest(color));
+        }
+    }
+
+    #[test]
+    fn test_lookup_follows_changes() {
+        let mut palette = Palette::from_colors(&[Color::BLACK, Color::WHITE]);
+        assert_eq!(palette.lookup(Color::new(250, 0, 0, 255)), 0);
+
+        palette.set(2, Color::new(255, 0, 0, 255));
+        assert_eq!(palette.lookup(Color::new(250, 0, 0, 255)), 2);
+    }
+
+    #[test]
+    fn test_raw_and_playpal_round_trip() {
+        let palette = grays();
+        let raw = palette.to_raw();
+        assert_eq!(raw.len(), RAW_SIZE);
+        assert_eq!(Palette::from_raw(&raw).unwrap(), palette);
+
+        let tinted = palette.tinted(Color::new(255, 0, 0, 255), 0.5);
+        let playpal = Palette::to_playpal(&[palette.clone(), tinted.clone()]);
+        assert_eq!(Palette::from_playpal(&playpal).unwrap(), [palette, tinted]);
+
+        assert!(matches!(
+            Palette::from_playpal(&raw[..700]),
+            Err(PaletteError::Size(700))
+        ));
+    }
+
+    #[test]
+    fn test_jasc_round_trip() {
+        let palette = grays();
+        assert_eq!(Palette::parse_jasc(&palette.to_jasc()).unwrap(), palette);
+
+        let short = Palette::parse_jasc("JASC-PAL\n0100\n2\n255 0 0\n0 0 255\n").unwrap();
+        assert_eq!(short.get(1), Color::new(0, 0, 255, 255));
+        assert_eq!(short.get(2), Color::BLACK);
+
+        let err = Palette::parse_jasc("JASC-PAL\n0100\n2\n255 0 0\n0 x 255\n").unwrap_err();
+        assert!(matches!(err, PaletteError::Parse { line: 5, .. }));
+
+        let err = Palette::parse_jasc("JASC-PAL\n").unwrap_err();
+        assert!(matches!(err, PaletteError::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_gpl_round_trip() {
+        let palette = grays();
+        assert_eq!(
+            Palette::parse_gpl(&palette.to_gpl("grays")).unwrap(),
+            palette
+        );
+
+        let text = "GIMP Palette\nName: Fire\nColumns: 4\n# comment\n255  64   0\tOrange\n";
+        let fire = Palette::parse_gpl(text).unwrap();
+        assert_eq!(fire.get(0), Color::new(255, 64, 0, 255));
+
+        assert!(Palette::parse_gpl("JASC-PAL\n").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_by_extension() {
+        let dir = std::env::temp_dir().join(format!("palette-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let palette = grays().tinted(Color::new(0, 128, 255, 255), 0.25);
+
+        for name in ["test.pal", "test.gpl", "test.lmp"] {
+            let path = dir.join(name);
+            palette.save(&path).unwrap();
+            assert_eq!(Palette::load(&path).unwrap(), palette, "{name}");
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
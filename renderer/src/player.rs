@@ -1,18 +1,107 @@
 use math::vector::Vector2;
 
+/// A field of view in degrees, fixed along one screen axis.
+///
+/// The other axis follows from the viewport's aspect ratio, so a wider
+/// window shows more of the world instead of stretching it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fov {
+    Horizontal(f32),
+    Vertical(f32),
+}
+
+impl Fov {
+    /// Returns the horizontal FOV in degrees for a viewport of `aspect` (width / height).
+    pub fn horizontal(self, aspect: f32) -> f32 {
+        match self {
+            Fov::Horizontal(deg) => deg,
+            Fov::Vertical(deg) => {
+                let half = (math::deg_to_rad(deg) / 2.0).tan() * aspect;
+                math::rad_to_deg(2.0 * half.atan())
+            }
+        }
+    }
+
+    /// Returns the vertical FOV in degrees for a viewport of `aspect` (width / height).
+    pub fn vertical(self, aspect: f32) -> f32 {
+        match self {
+            Fov::Vertical(deg) => deg,
+            Fov::Horizontal(deg) => {
+                let half = (math::deg_to_rad(deg) / 2.0).tan() / aspect;
+                math::rad_to_deg(2.0 * half.atan())
+            }
+        }
+    }
+}
+
 pub struct Player {
     pub pos: Vector2,
     pub dir: Vector2,
     pub plane: Vector2,
+    /// Vertical look, as a fraction of the screen height the horizon is shifted by.
+    /// Positive values look up, negative values look down.
+    pub pitch: f32,
+    /// The height of the eyes above the ground, in wall units.
+    pub z: f32,
+    /// Applied to `plane` by `set_fov` and `set_aspect`.
+    pub fov: Fov,
 }
 
 impl Player {
+    /// The default eye height: exactly halfway up a full wall.
+    pub const EYE_HEIGHT: f32 = 0.5;
+
+    /// How far the horizon can be sheared before the image distorts too much.
+    pub const MAX_PITCH: f32 = 0.5;
+
+    /// The default field of view: the classic `plane` length of 0.66, or
+    /// `2 * atan(0.66)` in degrees.
+    pub const DEFAULT_FOV: Fov = Fov::Horizontal(66.849_62);
+
     pub fn new(x: f32, y: f32) -> Self {
-        Self {
+        let mut player = Self {
             pos: Vector2::new(x, y),
             dir: Vector2::new(0.0, -1.0),
-            plane: Vector2::new(0.66, 0.0),
-        }
+            plane: Vector2::zero(),
+            pitch: 0.0,
+            z: Self::EYE_HEIGHT,
+            fov: Self::DEFAULT_FOV,
+        };
+        player.set_fov(Self::DEFAULT_FOV, 1.0);
+        player
+    }
+
+    /// Changes the field of view for a viewport of `aspect` (width / height).
+    pub fn set_fov(&mut self, fov: Fov, aspect: f32) {
+        self.fov = fov;
+        self.set_aspect(aspect);
+    }
+
+    /// Recomputes `plane` from `dir` and `fov` after the viewport changed shape.
+    ///
+    /// Only a `Fov::Vertical` depends on the aspect ratio.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        let half = math::deg_to_rad(self.fov.horizontal(aspect)) / 2.0;
+        let dir = self.dir.normalize();
+        self.plane = Vector2::new(-dir.y, dir.x).scale(half.tan());
+    }
+
+    /// Tilts the view up (positive) or down (negative), clamped to `MAX_PITCH`.
+    pub fn look(&mut self, amount: f32) {
+        self.pitch = math::clamp(self.pitch + amount, -Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Returns the screen row of the horizon for a viewport `height` pixels tall.
+    pub fn horizon(&self, height: usize) -> f32 {
+        height as f32 / 2.0 + self.pitch * height as f32
+    }
+
+    /// Faces the player along `angle` (radians, 0 = +X, PI / 2 = +Y), keeping
+    /// the current field of view.
+    pub fn set_angle(&mut self, angle: f32) {
+        let plane_len = self.plane.length();
+        self.dir = Vector2::new(angle.cos(), angle.sin());
+        self.plane = Vector2::new(-angle.sin(), angle.cos()).scale(plane_len);
     }
 
     pub fn rotate(&mut self, angle: f32) {
@@ -35,4 +124,50 @@ mod tests {
         let dot = p.dir.dot(&p.plane);
         assert!(dot.abs() < 1e-6);
     }
+
+    #[test]
+    fn test_set_angle_matches_default_facing() {
+        let mut p = Player::new(0.0, 0.0);
+        p.set_angle(math::deg_to_rad(-90.0));
+
+        let fresh = Player::new(0.0, 0.0);
+        assert!(p.dir.add(&fresh.dir.scale(-1.0)).length() < 1e-6);
+        assert!(p.plane.add(&fresh.plane.scale(-1.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_look_is_clamped() {
+        let mut p = Player::new(0.0, 0.0);
+        p.look(0.25);
+        assert_eq!(p.horizon(100), 75.0);
+
+        p.look(10.0);
+        assert_eq!(p.pitch, Player::MAX_PITCH);
+        p.look(-10.0);
+        assert_eq!(p.pitch, -Player::MAX_PITCH);
+    }
+
+    #[test]
+    fn test_fov_sets_plane_length() {
+        let mut p = Player::new(0.0, 0.0);
+        assert!((p.plane.length() - 0.66).abs() < 1e-5);
+
+        p.set_fov(Fov::Horizontal(90.0), 16.0 / 9.0);
+        assert!((p.plane.length() - 1.0).abs() < 1e-6);
+        assert!(p.dir.dot(&p.plane).abs() < 1e-6);
+
+        // A fixed vertical FOV widens the view on a wider screen
+        p.set_fov(Fov::Vertical(60.0), 4.0 / 3.0);
+        let narrow = p.plane.length();
+        p.set_aspect(16.0 / 9.0);
+        assert!(p.plane.length() > narrow);
+        assert!((p.plane.length() - (30f32.to_radians().tan() * 16.0 / 9.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_fov_axes_round_trip() {
+        let fov = Fov::Vertical(60.0);
+        let h = fov.horizontal(16.0 / 9.0);
+        assert!((Fov::Horizontal(h).vertical(16.0 / 9.0) - 60.0).abs() < 1e-3);
+    }
 }
@@ -0,0 +1,518 @@
+//! Screen-space effects applied to a finished frame.
+//!
+//! A `PostFx` holds a chain of `Effect`s that run in order over the
+//! framebuffer after `render` (and usually after the HUD). Every effect
+//! has a `Tween` controlling how strong it is, so flashes fade away and
+//! shakes settle on their own; `PostFx::update` drops effects once they
+//! have finished and no longer change the picture.
+
+use crate::{color::Color, framebuffer::FrameView};
+
+/// The shape of a `Tween` between its start and end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    /// Starts slow and speeds up.
+    In,
+    /// Starts fast and slows down, like a flash dying away.
+    Out,
+    InOut,
+}
+
+impl Ease {
+    /// Maps linear progress `t` (0.0 to 1.0) onto the curve.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = math::clamp(t, 0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::In => t * t,
+            Ease::Out => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A value that moves from `from` to `to` over `duration` seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub duration: f32,
+    pub ease: Ease,
+    elapsed: f32,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            ease: Ease::Linear,
+            elapsed: 0.0,
+        }
+    }
+
+    /// A value that never changes.
+    pub fn constant(value: f32) -> Self {
+        Self::new(value, value, 0.0)
+    }
+
+    pub fn with_ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration.max(0.0));
+    }
+
+    pub fn value(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return self.to;
+        }
+        let t = self.ease.apply(self.elapsed / self.duration);
+        self.from + (self.to - self.from) * t
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Returns `true` once the tween has settled at zero for good.
+    fn is_spent(&self) -> bool {
+        self.is_finished() && self.to == 0.0
+    }
+}
+
+/// Blends the whole frame towards a color: damage and pickup flashes,
+/// fades to and from black.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tint {
+    pub color: Color,
+    /// How much of `color` covers the frame (0.0 to 1.0).
+    pub amount: Tween,
+}
+
+impl Tint {
+    /// A flash that starts at `amount` and dies away over `duration` seconds.
+    pub fn flash(color: Color, amount: f32, duration: f32) -> Self {
+        Self {
+            color,
+            amount: Tween::new(amount, 0.0, duration).with_ease(Ease::Out),
+        }
+    }
+
+    /// Fades the frame to `color` over `duration` seconds and stays there.
+    pub fn fade_out(color: Color, duration: f32) -> Self {
+        Self {
+            color,
+            amount: Tween::new(0.0, 1.0, duration),
+        }
+    }
+
+    /// Fades in from a frame fully covered by `color`.
+    pub fn fade_in(color: Color, duration: f32) -> Self {
+        Self {
+            color,
+            amount: Tween::new(1.0, 0.0, duration),
+        }
+    }
+}
+
+/// Darkens every `spacing`th row, like the gaps between CRT scanlines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scanlines {
+    pub spacing: usize,
+    /// How much darker the gap rows are (0.0 to 1.0).
+    pub darkness: Tween,
+}
+
+/// Bulges the frame like the curved glass of a CRT. Corners pulled in
+/// from outside the frame are black.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crt {
+    /// 0.0 is flat; around 0.1 is a typical TV.
+    pub curvature: Tween,
+}
+
+/// Darkens the frame towards its edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vignette {
+    /// How dark the corners get (0.0 to 1.0).
+    pub strength: Tween,
+    /// Distance from the center, relative to the corners, where darkening starts.
+    pub radius: f32,
+}
+
+/// Splits the red and blue channels sideways, like a cheap lens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChromaticAberration {
+    /// Pixels red is moved right and blue left.
+    pub offset: Tween,
+}
+
+/// Jolts the frame around, e.g. for explosions and heavy landings.
+/// Uncovered edges are black.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shake {
+    /// The largest offset in pixels.
+    pub amplitude: Tween,
+    /// New offsets per second.
+    pub frequency: f32,
+    time: f32,
+}
+
+impl Shake {
+    /// A shake that starts at `amplitude` pixels and settles over `duration` seconds.
+    pub fn new(amplitude: f32, duration: f32) -> Self {
+        Self {
+            amplitude: Tween::new(amplitude, 0.0, duration).with_ease(Ease::Out),
+            frequency: 30.0,
+            time: 0.0,
+        }
+    }
+
+    /// Returns the offset the frame is currently moved by.
+    pub fn offset(&self) -> (i32, i32) {
+        let amplitude = self.amplitude.value();
+        let step = (self.time * self.frequency) as u32;
+        let x = (hash(step * 2) * 2.0 - 1.0) * amplitude;
+        let y = (hash(step * 2 + 1) * 2.0 - 1.0) * amplitude;
+        (x.round() as i32, y.round() as i32)
+    }
+}
+
+/// Returns a pseudo-random value in 0.0..1.0 for `n`.
+fn hash(n: u32) -> f32 {
+    let mut x = n.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    (x >> 8) as f32 / (1 << 24) as f32
+}
+
+/// One pass of the post-processing chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    Tint(Tint),
+    Scanlines(Scanlines),
+    Crt(Crt),
+    Vignette(Vignette),
+    ChromaticAberration(ChromaticAberration),
+    Shake(Shake),
+}
+
+impl Effect {
+    /// Advances the effect's animations by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        match self {
+            Effect::Tint(tint) => tint.amount.update(dt),
+            Effect::Scanlines(scanlines) => scanlines.darkness.update(dt),
+            Effect::Crt(crt) => crt.curvature.update(dt),
+            Effect::Vignette(vignette) => vignette.strength.update(dt),
+            Effect::ChromaticAberration(aberration) => aberration.offset.update(dt),
+            Effect::Shake(shake) => {
+                shake.amplitude.update(dt);
+                shake.time += dt;
+            }
+        }
+    }
+
+    /// Returns `true` once the effect has finished animating to a point
+    /// where it leaves the frame untouched.
+    pub fn is_done(&self) -> bool {
+        match self {
+            Effect::Tint(tint) => tint.amount.is_spent(),
+            Effect::Scanlines(scanlines) => scanlines.darkness.is_spent(),
+            Effect::Crt(crt) => crt.curvature.is_spent(),
+            Effect::Vignette(vignette) => vignette.strength.is_spent(),
+            Effect::ChromaticAberration(aberration) => aberration.offset.is_spent(),
+            Effect::Shake(shake) => shake.amplitude.is_spent(),
+        }
+    }
+
+    /// Applies the effect to `target` in place. Effects that move pixels
+    /// around copy the frame into `scratch` first.
+    pub fn apply(&self, target: &mut FrameView, scratch: &mut Vec<u32>) {
+        let format = target.format();
+        let (width, height) = (target.width(), target.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        match self {
+            Effect::Tint(tint) => {
+                let amount = math::clamp(tint.amount.value(), 0.0, 1.0);
+                if amount <= 0.0 {
+                    return;
+                }
+                map_pixels(target, |_, _, color| color.lerp(&tint.color, amount));
+            }
+            Effect::Scanlines(scanlines) => {
+                let keep = 1.0 - math::clamp(scanlines.darkness.value(), 0.0, 1.0);
+                let spacing = scanlines.spacing.max(1);
+                for y in (spacing - 1..height).step_by(spacing) {
+                    for pixel in target.row_mut(y) {
+                        *pixel = format.pack(format.unpack(*pixel).darkened(keep));
+                    }
+                }
+            }
+            Effect::Crt(crt) => {
+                let k = crt.curvature.value();
+                let black = format.pack(Color::BLACK);
+                let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+                copy_frame(target, scratch);
+                for y in 0..height {
+                    let v = (y as f32 + 0.5 - cy) / cy;
+                    for (x, pixel) in target.row_mut(y).iter_mut().enumerate() {
+                        let u = (x as f32 + 0.5 - cx) / cx;
+                        let bulge = 1.0 + k * (u * u + v * v);
+                        let (sx, sy) = (u * bulge * cx + cx, v * bulge * cy + cy);
+                        *pixel =
+                            if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                                black
+                            } else {
+                                scratch[sy as usize * width + sx as usize]
+                            };
+                    }
+                }
+            }
+            Effect::Vignette(vignette) => {
+                let strength = math::clamp(vignette.strength.value(), 0.0, 1.0);
+                if strength <= 0.0 {
+                    return;
+                }
+                let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+                let corner = (cx * cx + cy * cy).sqrt();
+                let radius = math::clamp(vignette.radius, 0.0, 1.0);
+                map_pixels(target, |x, y, color| {
+                    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                    let distance = (dx * dx + dy * dy).sqrt() / corner;
+                    let t = if radius >= 1.0 {
+                        0.0
+                    } else {
+                        math::clamp((distance - radius) / (1.0 - radius), 0.0, 1.0)
+                    };
+                    // Smoothstep, so the edge of the darkening is not visible
+                    let t = t * t * (3.0 - 2.0 * t);
+                    color.darkened(1.0 - strength * t)
+                });
+            }
+            Effect::ChromaticAberration(aberration) => {
+                let offset = aberration.offset.value().round() as isize;
+                if offset == 0 {
+                    return;
+                }
+                copy_frame(target, scratch);
+                let sample = |row: &[u32], x: isize| {
+                    format.unpack(row[x.clamp(0, width as isize - 1) as usize])
+                };
+                for y in 0..height {
+                    let source = &scratch[y * width..(y + 1) * width];
+                    for (x, pixel) in target.row_mut(y).iter_mut().enumerate() {
+                        let x = x as isize;
+                        let mut color = format.unpack(source[x as usize]);
+                        color.r = sample(source, x - offset).r;
+                        color.b = sample(source, x + offset).b;
+                        *pixel = format.pack(color);
+                    }
+                }
+            }
+            Effect::Shake(shake) => {
+                let (dx, dy) = shake.offset();
+                if (dx, dy) == (0, 0) {
+                    return;
+                }
+                let black = format.pack(Color::BLACK);
+                copy_frame(target, scratch);
+                for y in 0..height {
+                    let sy = y as i32 - dy;
+                    for (x, pixel) in target.row_mut(y).iter_mut().enumerate() {
+                        let sx = x as i32 - dx;
+                        let inside = sx >= 0 && sy >= 0 && (sx as usize) < width;
+                        *pixel = if inside && (sy as usize) < height {
+                            scratch[sy as usize * width + sx as usize]
+                        } else {
+                            black
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replaces every pixel of `target` with `f(x, y, color)`.
+fn map_pixels(target: &mut FrameView, f: impl Fn(usize, usize, Color) -> Color) {
+    let format = target.format();
+    for y in 0..target.height() {
+        for (x, pixel) in target.row_mut(y).iter_mut().enumerate() {
+            *pixel = format.pack(f(x, y, format.unpack(*pixel)));
+        }
+    }
+}
+
+/// Copies `target` into `scratch` as tightly packed rows.
+fn copy_frame(target: &mut FrameView, scratch: &mut Vec<u32>) {
+    scratch.clear();
+    for y in 0..target.height() {
+        scratch.extend_from_slice(target.row_mut(y));
+    }
+}
+
+/// A chain of effects, applied in the order they were added.
+#[derive(Clone, Debug, Default)]
+pub struct PostFx {
+    pub effects: Vec<Effect>,
+    scratch: Vec<u32>,
+}
+
+impl PostFx {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, effect: Effect) {
+        self.effects.push(effect);
+    }
+
+    /// Advances every effect by `dt` seconds and drops the finished ones.
+    pub fn update(&mut self, dt: f32) {
+        for effect in &mut self.effects {
+            effect.update(dt);
+        }
+        self.effects.retain(|effect| !effect.is_done());
+    }
+
+    /// Runs the chain over `target`.
+    pub fn apply(&mut self, target: &mut FrameView) {
+        for effect in &self.effects {
+            effect.apply(target, &mut self.scratch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::{Framebuffer, PixelFormat};
+
+    const GRAY: Color = Color::from_hex(0x808080);
+
+    fn frame(color: Color) -> Framebuffer {
+        let mut fb = Framebuffer::new(8, 8, PixelFormat::Argb32);
+        fb.clear(color);
+        fb
+    }
+
+    #[test]
+    fn test_tween() {
+        let mut tween = Tween::new(1.0, 0.0, 2.0);
+        assert_eq!(tween.value(), 1.0);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 0.5);
+        tween.update(5.0);
+        assert_eq!(tween.value(), 0.0);
+        assert!(tween.is_finished());
+
+        assert_eq!(Tween::constant(3.0).value(), 3.0);
+        assert_eq!(Ease::Out.apply(0.5), 0.75);
+    }
+
+    #[test]
+    fn test_flash_fades_and_is_dropped() {
+        let red = Color::from_hex(0xFF0000);
+        let mut postfx = PostFx::new();
+        postfx.push(Effect::Tint(Tint::flash(red, 1.0, 0.5)));
+
+        let mut fb = frame(GRAY);
+        postfx.apply(&mut fb.view());
+        assert_eq!(fb.get_color(3, 3), Some(red));
+
+        postfx.update(1.0);
+        assert!(postfx.effects.is_empty());
+    }
+
+    #[test]
+    fn test_fade_out_stays() {
+        let mut postfx = PostFx::new();
+        postfx.push(Effect::Tint(Tint::fade_out(Color::BLACK, 1.0)));
+        postfx.update(2.0);
+        assert_eq!(postfx.effects.len(), 1);
+
+        let mut fb = frame(GRAY);
+        postfx.apply(&mut fb.view());
+        assert_eq!(fb.get_color(0, 0), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_scanlines_darken_every_other_row() {
+        let effect = Effect::Scanlines(Scanlines {
+            spacing: 2,
+            darkness: Tween::constant(1.0),
+        });
+        let mut fb = frame(GRAY);
+        effect.apply(&mut fb.view(), &mut Vec::new());
+        assert_eq!(fb.get_color(0, 0), Some(GRAY));
+        assert_eq!(fb.get_color(0, 1), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn test_vignette_keeps_the_center() {
+        let effect = Effect::Vignette(Vignette {
+            strength: Tween::constant(1.0),
+            radius: 0.5,
+        });
+        let mut fb = frame(Color::WHITE);
+        effect.apply(&mut fb.view(), &mut Vec::new());
+        assert_eq!(fb.get_color(4, 4), Some(Color::WHITE));
+        assert!(fb.get_color(0, 0).unwrap().r < 64);
+    }
+
+    #[test]
+    fn test_crt_blacks_out_corners() {
+        let effect = Effect::Crt(Crt {
+            curvature: Tween::constant(0.3),
+        });
+        let mut fb = frame(GRAY);
+        effect.apply(&mut fb.view(), &mut Vec::new());
+        assert_eq!(fb.get_color(0, 0), Some(Color::BLACK));
+        assert_eq!(fb.get_color(4, 4), Some(GRAY));
+    }
+
+    #[test]
+    fn test_chromatic_aberration_splits_channels() {
+        let mut fb = frame(Color::BLACK);
+        fb.view().put(4, 0, Color::WHITE);
+        let effect = Effect::ChromaticAberration(ChromaticAberration {
+            offset: Tween::constant(1.0),
+        });
+        effect.apply(&mut fb.view(), &mut Vec::new());
+
+        assert_eq!(fb.get_color(4, 0), Some(Color::from_hex(0x00FF00)));
+        assert_eq!(fb.get_color(5, 0), Some(Color::from_hex(0xFF0000)));
+        assert_eq!(fb.get_color(3, 0), Some(Color::from_hex(0x0000FF)));
+    }
+
+    #[test]
+    fn test_shake_moves_the_frame_and_settles() {
+        let mut shake = Shake::new(3.0, 1.0);
+        let (dx, dy) = shake.offset();
+        assert!(dx.abs() <= 3 && dy.abs() <= 3);
+
+        let mut effect = Effect::Shake(shake);
+        let mut fb = frame(Color::BLACK);
+        fb.view().put(4, 4, Color::WHITE);
+        effect.apply(&mut fb.view(), &mut Vec::new());
+        let x = (4 + dx) as usize;
+        let y = (4 + dy) as usize;
+        assert_eq!(fb.get_color(x, y), Some(Color::WHITE));
+
+        effect.update(2.0);
+        assert!(effect.is_done());
+        shake.amplitude.update(2.0);
+        assert_eq!(shake.offset(), (0, 0));
+    }
+}
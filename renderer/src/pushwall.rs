@@ -0,0 +1,210 @@
+/// The lifecycle of a secret push-wall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PushWallState {
+    /// Waiting in its original cell for the player to push it.
+    Idle,
+    /// Sliding away from the player. `offset` is measured in cells from the origin.
+    Moving,
+    /// Came to rest in its final cell. It cannot be pushed again.
+    Settled,
+}
+
+/// A wall block that slides one or more cells when the player pushes it.
+///
+/// While the block is `Moving`, it is not stored in the `Map` grid at its
+/// fractional position. Instead, the cells it currently overlaps are kept
+/// solid for collision purposes, and `Ray::cast` intersects the offset block
+/// directly.
+#[derive(Clone, Debug)]
+pub struct PushWall {
+    /// The tile value the block carries (its texture / color).
+    pub tile: u8,
+
+    /// The X-coordinate of the cell the block started in.
+    pub origin_x: u32,
+
+    /// The Y-coordinate of the cell the block started in.
+    pub origin_y: u32,
+
+    /// The direction of travel on the X-axis (-1, 0 or 1).
+    pub dir_x: i32,
+
+    /// The direction of travel on the Y-axis (-1, 0 or 1).
+    pub dir_y: i32,
+
+    /// How many cells the block travels before settling.
+    pub cells: u32,
+
+    /// The sliding speed, in cells per second.
+    pub speed: f32,
+
+    /// The distance travelled so far, in cells (0.0 to `cells`).
+    pub offset: f32,
+
+    pub state: PushWallState,
+}
+
+impl PushWall {
+    /// The default sliding speed, in cells per second.
+    pub const DEFAULT_SPEED: f32 = 1.0;
+
+    pub fn new(tile: u8, origin_x: u32, origin_y: u32, cells: u32) -> Self {
+        Self {
+            tile,
+            origin_x,
+            origin_y,
+            dir_x: 0,
+            dir_y: 0,
+            cells,
+            speed: Self::DEFAULT_SPEED,
+            offset: 0.0,
+            state: PushWallState::Idle,
+        }
+    }
+
+    /// Returns the integer cell the leading edge of the block currently occupies.
+    ///
+    /// This is the cell that must be treated as solid while the block moves.
+    pub fn current_cell(&self) -> (i32, i32) {
+        let travelled = self.offset.ceil() as i32;
+        (
+            self.origin_x as i32 + self.dir_x * travelled,
+            self.origin_y as i32 + self.dir_y * travelled,
+        )
+    }
+
+    /// Returns the cells the block overlaps: the one its trailing edge is in,
+    /// then the one its leading edge is in. Both are the same cell when the
+    /// block is aligned to the grid.
+    pub fn occupied_cells(&self) -> [(i32, i32); 2] {
+        let trailing = self.offset.floor() as i32;
+        let trailing = (
+            self.origin_x as i32 + self.dir_x * trailing,
+            self.origin_y as i32 + self.dir_y * trailing,
+        );
+        [trailing, self.current_cell()]
+    }
+
+    /// Returns the top-left corner of the block in world units.
+    pub fn position(&self) -> (f32, f32) {
+        (
+            self.origin_x as f32 + self.dir_x as f32 * self.offset,
+            self.origin_y as f32 + self.dir_y as f32 * self.offset,
+        )
+    }
+
+    /// Returns `true` if the moving block overlaps the given cell.
+    pub fn covers(&self, x: i32, y: i32) -> bool {
+        if self.state != PushWallState::Moving {
+            return false;
+        }
+        let (px, py) = self.position();
+        let (fx, fy) = (x as f32, y as f32);
+        px < fx + 1.0 && px + 1.0 > fx && py < fy + 1.0 && py + 1.0 > fy
+    }
+
+    /// Intersects a ray against the (possibly offset) unit block.
+    ///
+    /// `origin` and `dir` describe the ray as `origin + t * dir`. Returns the
+    /// entry parameter `t` and whether the hit face is vertical (an X-side),
+    /// using the same conventions as `CastResult`.
+    pub fn intersect(&self, origin: (f32, f32), dir: (f32, f32)) -> Option<(f32, bool)> {
+        let (px, py) = self.position();
+
+        let (tx_near, tx_far) = slab(origin.0, dir.0, px, px + 1.0)?;
+        let (ty_near, ty_far) = slab(origin.1, dir.1, py, py + 1.0)?;
+
+        let t_enter = tx_near.max(ty_near);
+        let t_exit = tx_far.min(ty_far);
+
+        if t_enter > t_exit || t_exit <= 0.0 {
+            return None;
+        }
+
+        Some((t_enter, tx_near > ty_near))
+    }
+
+    /// Advances the block by `dt` seconds.
+    ///
+    /// Returns `true` on the frame the block settles.
+    pub fn update(&mut self, dt: f32) -> bool {
+        if self.state != PushWallState::Moving {
+            return false;
+        }
+
+        self.offset += self.speed * dt;
+        if self.offset >= self.cells as f32 {
+            self.offset = self.cells as f32;
+            self.state = PushWallState::Settled;
+            return true;
+        }
+        false
+    }
+}
+
+/// Computes the entry and exit parameters of a ray along one axis of an AABB.
+///
+/// Returns `None` if the ray is parallel to the slab and outside of it.
+fn slab(origin: f32, dir: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if dir == 0.0 {
+        if origin < min || origin > max {
+            return None;
+        }
+        return Some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+
+    let t1 = (min - origin) / dir;
+    let t2 = (max - origin) / dir;
+    Some((t1.min(t2), t1.max(t2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moving_east(offset: f32) -> PushWall {
+        let mut wall = PushWall::new(1, 2, 0, 2);
+        wall.dir_x = 1;
+        wall.state = PushWallState::Moving;
+        wall.offset = offset;
+        wall
+    }
+
+    #[test]
+    fn test_update_settles() {
+        let mut wall = moving_east(0.0);
+        assert!(!wall.update(1.5));
+        assert_eq!(wall.state, PushWallState::Moving);
+
+        assert!(wall.update(1.0));
+        assert_eq!(wall.state, PushWallState::Settled);
+        assert_eq!(wall.offset, 2.0);
+        assert_eq!(wall.current_cell(), (4, 0));
+    }
+
+    #[test]
+    fn test_covers_both_cells_while_sliding() {
+        let wall = moving_east(0.5);
+        assert!(wall.covers(2, 0));
+        assert!(wall.covers(3, 0));
+        assert!(!wall.covers(4, 0));
+        assert_eq!(wall.current_cell(), (3, 0));
+        assert_eq!(wall.occupied_cells(), [(2, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn test_intersect_offset_face() {
+        // Block at x = 2.25..3.25, looking East from x = 0.5
+        let wall = moving_east(0.25);
+        let (t, vertical) = wall.intersect((0.5, 0.5), (1.0, 0.0)).unwrap();
+        assert!((t - 1.75).abs() < 1e-6);
+        assert!(vertical);
+    }
+
+    #[test]
+    fn test_intersect_miss() {
+        let wall = moving_east(0.25);
+        // Ray running parallel, one row below the block
+        assert!(wall.intersect((0.5, 1.5), (1.0, 0.0)).is_none());
+    }
+}
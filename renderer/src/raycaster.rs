@@ -6,6 +6,10 @@ pub struct CastResult {
     pub distance: f32,
     pub wall_value: u8,
     pub hit_vertical: bool,
+    /// The X-coordinate of the tile that was hit.
+    pub map_x: i32,
+    /// The Y-coordinate of the tile that was hit.
+    pub map_y: i32,
 }
 
 /// Represents a single ray being cast from the player's position into the 2D map grid.
@@ -13,6 +17,9 @@ pub struct CastResult {
 /// This struct uses the Digital Differential Analyzer (DDA) algorithm to efficiently
 /// find the first intersection between the ray and a solid wall on a discrete grid.
 pub struct Ray {
+    /// The position the ray was cast from.
+    pub origin: Vector2,
+
     /// The normalized direction vector of the ray.
     pub dir: Vector2,
 
@@ -70,6 +77,7 @@ impl Ray {
         };
 
         Self {
+            origin: player_pos,
             dir: ray_dir,
             delta_dist: Vector2::new(delta_dist_x, delta_dist_y),
             side_dist: Vector2::new(side_dist_x, side_dist_y),
@@ -80,7 +88,21 @@ impl Ray {
         }
     }
 
+    /// Steps the ray until it hits a solid wall.
+    ///
+    /// The ray keeps its state, so calling `cast` again continues past the
+    /// previous hit towards the next wall.
     pub fn cast(&mut self, map: &Map) -> Option<CastResult> {
+        self.march(map, |tile, _| tile > 0)
+    }
+
+    /// Steps the ray until it hits anything that rises above the ground:
+    /// solid walls as well as empty tiles with a raised floor (platforms).
+    pub fn cast_raised(&mut self, map: &Map) -> Option<CastResult> {
+        self.march(map, |tile, floor| tile > 0 || floor > 0.0)
+    }
+
+    fn march(&mut self, map: &Map, is_hit: impl Fn(u8, f32) -> bool) -> Option<CastResult> {
         let mut hit_vertical;
 
         loop {
@@ -100,10 +122,26 @@ impl Ray {
                 return None;
             }
 
-            // 2. Check Map
-            match map.get_tile(self.map_x as u32, self.map_y as u32) {
-                Some(tile) if tile > 0 => {
-                    // 3. Calculate perpendicular distance
+            // 2. Moving push-walls are offset from the grid, so intersect the block itself
+            if let Some(wall) = map.pushwall_at(self.map_x, self.map_y) {
+                let origin = (self.origin.x, self.origin.y);
+                if let Some((dist, vertical)) = wall.intersect(origin, (self.dir.x, self.dir.y)) {
+                    return Some(CastResult {
+                        distance: dist,
+                        wall_value: wall.tile,
+                        hit_vertical: vertical,
+                        map_x: self.map_x,
+                        map_y: self.map_y,
+                    });
+                }
+                continue;
+            }
+
+            // 3. Check Map
+            let (x, y) = (self.map_x as u32, self.map_y as u32);
+            match map.get_tile(x, y) {
+                Some(tile) if is_hit(tile, map.get_floor_height(x, y).unwrap_or(0.0)) => {
+                    // 4. Calculate perpendicular distance
                     let dist = if hit_vertical {
                         self.side_dist.x - self.delta_dist.x
                     } else {
@@ -114,9 +152,11 @@ impl Ray {
                         distance: dist,
                         wall_value: tile,
                         hit_vertical,
+                        map_x: self.map_x,
+                        map_y: self.map_y,
                     });
                 }
-                Some(_) => continue, // It's an empty floor (0), keep jumping!
+                Some(_) => continue, // Nothing in the way, keep jumping!
                 None => return None, // We flew off the map
             }
         }
@@ -171,11 +211,9 @@ mod tests {
 
     #[test]
     fn test_cast() {
-        let map = Map {
-            width: 2,
-            height: 2,
-            grid: vec![0, 0, 255, 0, 0, 255],
-        };
+        let mut map = Map::new(3, 2);
+        map.set_tile(2, 0, 255);
+        map.set_tile(2, 1, 255);
         let pos = Vector2::new(0.5, 0.5);
         let dir = Vector2::new(1.0, 0.0);
         let mut ray = Ray::new(pos, dir);
@@ -184,4 +222,35 @@ mod tests {
         assert!(res.is_some());
         assert_eq!(res.unwrap().distance, 1.5);
     }
+
+    #[test]
+    fn test_cast_continues_past_hit() {
+        let mut map = Map::new(6, 1);
+        map.set_tile(2, 0, 1);
+        map.set_floor_height(3, 0, 0.5);
+        map.set_tile(4, 0, 2);
+
+        let mut ray = Ray::new(Vector2::new(0.5, 0.5), Vector2::new(1.0, 0.0));
+        assert_eq!(ray.cast_raised(&map).unwrap().map_x, 2);
+        let platform = ray.cast_raised(&map).unwrap();
+        assert_eq!((platform.map_x, platform.wall_value), (3, 0));
+        assert_eq!(ray.cast_raised(&map).unwrap().map_x, 4);
+        assert!(ray.cast_raised(&map).is_none());
+    }
+
+    #[test]
+    fn test_cast_moving_pushwall() {
+        let mut map = Map::new(6, 1);
+        map.set_tile(2, 0, 3);
+        map.add_pushwall(2, 0, 2);
+        map.push(2, 0, 1, 0);
+        map.update(0.25);
+
+        // The block now spans x = 2.25..3.25
+        let mut ray = Ray::new(Vector2::new(0.5, 0.5), Vector2::new(1.0, 0.0));
+        let res = ray.cast(&map).unwrap();
+        assert!((res.distance - 1.75).abs() < 1e-6);
+        assert_eq!(res.wall_value, 3);
+        assert!(res.hit_vertical);
+    }
 }
@@ -0,0 +1,1045 @@
+use math::{clamp, vector::Vector2};
+
+use crate::{
+    color::Color,
+    columns::ColumnBuffer,
+    fog::Fog,
+    framebuffer::{FrameView, PixelFormat},
+    indexed::{IndexedFrame, IndexedMode},
+    light::{self, PointLight},
+    map::Map,
+    player::Player,
+    raycaster::Ray,
+    sky::Sky,
+    texture::{Filter, Texture},
+};
+
+const CEILING: Color = Color::from_hex(0x333333);
+const FLOOR: Color = Color::from_hex(0x111111);
+const WALL: Color = Color::from_hex(0xCC0000);
+const WALL_SIDE: Color = Color::from_hex(0x880000);
+const RISER: Color = Color::from_hex(0x7F8C8D);
+const RISER_SIDE: Color = Color::from_hex(0x545D5E);
+
+/// The flat surface colors, looked up once per frame in indexed mode.
+const SURFACES: [Color; 6] = [CEILING, FLOOR, WALL, WALL_SIDE, RISER, RISER_SIDE];
+
+/// Textured walls facing along the x axis are drawn this much darker,
+/// like the flat `WALL_SIDE`.
+const SIDE_LIGHT: f32 = 0.75;
+
+/// How `render_with` lays out pixels while the columns are being drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderLayout {
+    /// Draw into a contiguous column-major buffer, then transpose it into
+    /// the target in cache-sized blocks.
+    #[default]
+    ColumnMajor,
+    /// Write each column straight into the row-major target.
+    RowMajor,
+}
+
+/// Settings that change how a frame is drawn without changing the scene itself.
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Distance fog and light diminishing, applied to walls, floors and ceilings.
+    pub fog: Fog,
+
+    /// Modulates walls, floors and ceilings by the `Map` light levels and `lights`.
+    pub lighting: bool,
+
+    /// Dynamic lights for this frame, added on top of the baked light levels.
+    pub lights: Vec<PointLight>,
+
+    /// Drawn instead of the ceiling above tiles that are not `Map::covered`.
+    pub sky: Option<Sky>,
+
+    /// The intermediate pixel layout. Both produce identical frames.
+    pub layout: RenderLayout,
+
+    /// Wall textures, `wall_textures[n - 1]` for tile value `n`. Walls
+    /// without one keep the flat colors. Every block face maps the whole
+    /// texture once, and each texture's own `wrap`, `filter` and mip chain
+    /// decide how it is sampled within the face.
+    pub wall_textures: Vec<Texture>,
+
+    /// Drawn instead of the flat floor color, one texture per map tile.
+    pub floor_texture: Option<Texture>,
+
+    /// Drawn instead of the flat ceiling color, one texture per map tile.
+    pub ceiling_texture: Option<Texture>,
+}
+
+impl RenderOptions {
+    /// Returns the texture for walls with tile value `wall_value`.
+    pub fn wall_texture(&self, wall_value: u8) -> Option<&Texture> {
+        let index = (wall_value as usize).checked_sub(1)?;
+        self.wall_textures.get(index)
+    }
+}
+
+/// Columns handed to each worker in one go by `render_parallel`.
+#[cfg(feature = "parallel")]
+const STRIP_WIDTH: usize = 16;
+
+/// Renders a frame with the default `RenderOptions`.
+pub fn render(player: &Player, map: &Map, target: &mut FrameView) {
+    render_with(player, map, &RenderOptions::default(), target);
+}
+
+/// Renders a frame into `target`, filling the whole view.
+///
+/// The default column-major layout allocates a frame-sized buffer on every
+/// call. Keep a `Renderer` to reuse it from one frame to the next.
+pub fn render_with(player: &Player, map: &Map, options: &RenderOptions, target: &mut FrameView) {
+    Renderer::new().render(player, map, options, target);
+}
+
+/// Draws frames like `render_with`, keeping its intermediate buffer alive
+/// between them so rendering does not allocate every frame.
+pub struct Renderer {
+    columns: ColumnBuffer,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            columns: ColumnBuffer::new(0, 0),
+        }
+    }
+
+    /// Renders a frame into `target`, filling the whole view.
+    pub fn render(
+        &mut self,
+        player: &Player,
+        map: &Map,
+        options: &RenderOptions,
+        target: &mut FrameView,
+    ) {
+        match options.layout {
+            RenderLayout::ColumnMajor => {
+                self.columns.resize(target.width(), target.height());
+                render_columns(player, map, options, target.format(), &mut self.columns);
+                self.columns.transpose_into(target);
+            }
+            RenderLayout::RowMajor => render_rows(player, map, options, target),
+        }
+    }
+
+    /// Renders a frame like `render`, spreading strips of columns over the
+    /// rayon thread pool. The output is identical to the single-threaded path.
+    #[cfg(feature = "parallel")]
+    pub fn render_parallel(
+        &mut self,
+        player: &Player,
+        map: &Map,
+        options: &RenderOptions,
+        target: &mut FrameView,
+    ) {
+        use rayon::prelude::*;
+
+        let frame = Frame::new(
+            player,
+            map,
+            options,
+            target.width(),
+            target.height(),
+            Output::Packed(target.format()),
+        );
+        if frame.width == 0 || frame.height == 0 {
+            return;
+        }
+
+        // Every column is independent, so each worker fills its own strip of
+        // the column-major buffer
+        self.columns.resize(frame.width, frame.height);
+        self.columns
+            .pixels_mut()
+            .par_chunks_mut(frame.height * STRIP_WIDTH)
+            .enumerate()
+            .for_each(|(strip, chunk)| {
+                for (i, column) in chunk.chunks_exact_mut(frame.height).enumerate() {
+                    frame.draw_column(strip * STRIP_WIDTH + i, column);
+                }
+            });
+
+        self.columns.transpose_into(target);
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders a frame by writing each column straight into the row-major `target`.
+fn render_rows(player: &Player, map: &Map, options: &RenderOptions, target: &mut FrameView) {
+    let frame = Frame::new(
+        player,
+        map,
+        options,
+        target.width(),
+        target.height(),
+        Output::Packed(target.format()),
+    );
+    let mut column = vec![0; frame.height];
+
+    // Cast rays for every horizontal pixel
+    for x in 0..frame.width {
+        frame.draw_column(x, &mut column);
+        for (y, &pixel) in column.iter().enumerate() {
+            target.set(x, y, pixel);
+        }
+    }
+}
+
+/// Renders a frame into a column-major buffer, with pixels packed as `format`.
+///
+/// Use this instead of `render_with` to keep one `ColumnBuffer` alive across
+/// frames, then call `ColumnBuffer::transpose_into` to present it.
+pub fn render_columns(
+    player: &Player,
+    map: &Map,
+    options: &RenderOptions,
+    format: PixelFormat,
+    columns: &mut ColumnBuffer,
+) {
+    let frame = Frame::new(
+        player,
+        map,
+        options,
+        columns.width(),
+        columns.height(),
+        Output::Packed(format),
+    );
+
+    // Cast rays for every horizontal pixel
+    for x in 0..frame.width {
+        frame.draw_column(x, columns.column_mut(x));
+    }
+}
+
+/// Renders a frame like `render_with`, spreading strips of columns over the
+/// rayon thread pool. The output is identical to the single-threaded path.
+///
+/// Allocates a frame-sized buffer on every call. Keep a `Renderer` and use
+/// `Renderer::render_parallel` to reuse it from one frame to the next.
+#[cfg(feature = "parallel")]
+pub fn render_parallel(
+    player: &Player,
+    map: &Map,
+    options: &RenderOptions,
+    target: &mut FrameView,
+) {
+    Renderer::new().render_parallel(player, map, options, target);
+}
+
+/// Renders a frame in 8-bit palette indices, shaded through `mode.colormaps`.
+///
+/// Walls with a texture in `mode` are textured, everything else uses the
+/// palette entry closest to its true-color surface color. Colored lights
+/// only contribute their brightness, and fog fades towards the colormaps'
+/// fade color rather than `Fog::color`. Call `IndexedFrame::present` to
+/// convert the result to true color.
+pub fn render_indexed(
+    player: &Player,
+    map: &Map,
+    options: &RenderOptions,
+    mode: &IndexedMode,
+    target: &mut IndexedFrame,
+) {
+    let frame = Frame::new(
+        player,
+        map,
+        options,
+        target.width(),
+        target.height(),
+        Output::Indexed(mode),
+    );
+    let mut column = vec![0; frame.height];
+
+    // Cast rays for every horizontal pixel
+    for x in 0..frame.width {
+        frame.draw_column(x, &mut column);
+        for (index, &pixel) in target.column_mut(x).iter_mut().zip(&column) {
+            *index = pixel as u8;
+        }
+    }
+}
+
+/// What the `u32`s a `Frame` draws hold.
+#[derive(Clone, Copy)]
+enum Output<'a> {
+    /// True-color pixels packed as the given format.
+    Packed(PixelFormat),
+    /// Palette indices.
+    Indexed(&'a IndexedMode),
+}
+
+/// Everything a column needs to know about the frame being drawn.
+struct Frame<'a> {
+    player: &'a Player,
+    map: &'a Map,
+    options: &'a RenderOptions,
+    width: usize,
+    height: usize,
+    output: Output<'a>,
+    /// The palette indices of `SURFACES` in indexed mode
+    surfaces: [u8; 6],
+    /// The horizon moves up and down with the player's pitch (y-shearing)
+    horizon: f32,
+    /// Pixels per world unit at distance 1. Derived from the horizontal FOV
+    /// so vertical and horizontal scale match and walls stay square.
+    focal: f32,
+    /// Nothing can poke out from behind a column this tall
+    max_top: f32,
+    /// Lit or textured floors and ceilings, and the sky, depend on where each pixel lands in the map
+    per_pixel: bool,
+    /// Otherwise every column shares the same floor and ceiling, one packed color per row
+    rows: Vec<u32>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(
+        player: &'a Player,
+        map: &'a Map,
+        options: &'a RenderOptions,
+        width: usize,
+        height: usize,
+        output: Output<'a>,
+    ) -> Self {
+        let horizon = player.horizon(height);
+        let focal = width as f32 / (2.0 * player.plane.length().max(f32::EPSILON));
+        let per_pixel = options.lighting
+            || options.sky.is_some()
+            || options.floor_texture.is_some()
+            || options.ceiling_texture.is_some();
+
+        let surfaces = match output {
+            Output::Packed(_) => [0; 6],
+            Output::Indexed(mode) => SURFACES.map(|color| mode.palette.nearest(color)),
+        };
+
+        let mut frame = Self {
+            player,
+            map,
+            options,
+            width,
+            height,
+            output,
+            surfaces,
+            horizon,
+            focal,
+            max_top: map.max_column_top(),
+            per_pixel,
+            rows: Vec::new(),
+        };
+        if !per_pixel {
+            frame.rows = (0..height)
+                .map(|y| {
+                    let (base, dist) = surface_at(player, y, horizon, focal);
+                    frame.shade(base, None, dist)
+                })
+                .collect();
+        }
+        frame
+    }
+
+    /// Returns the output pixel for a flat surface color seen at `distance`,
+    /// optionally modulated by `light`.
+    fn shade(&self, base: Color, light: Option<Color>, distance: f32) -> u32 {
+        match self.output {
+            Output::Packed(format) => {
+                let base = light.map_or(base, |light| base.multiply(&light));
+                format.pack(self.options.fog.shade(base, distance))
+            }
+            Output::Indexed(mode) => {
+                let index = match SURFACES.iter().position(|&c| c == base) {
+                    Some(i) => self.surfaces[i],
+                    None => mode.palette.lookup(base),
+                };
+                let brightness = self.brightness(light, distance);
+                mode.colormaps.shade(index, brightness) as u32
+            }
+        }
+    }
+
+    /// Returns how lit an indexed pixel is, folding the light level,
+    /// diminishing and fog into the single value the colormaps understand.
+    fn brightness(&self, light: Option<Color>, distance: f32) -> f32 {
+        let fog = &self.options.fog;
+        let light = light.map_or(1.0, |l| l.r.max(l.g).max(l.b) as f32 / 255.0);
+        light * fog.light_at(distance) * (1.0 - fog.density_at(distance))
+    }
+
+    /// Draws screen column `x` into `column`, which is `height` pixels long.
+    fn draw_column(&self, x: usize, column: &mut [u32]) {
+        let Frame {
+            player,
+            map,
+            options,
+            height,
+            horizon,
+            focal,
+            ..
+        } = *self;
+
+        // Calculate the ray direction based on the player's plane
+        let camera_x = 2.0 * (x as f32) / (self.width as f32) - 1.0;
+        let ray_dir = player.dir.add(&player.plane.scale(camera_x));
+
+        let mut ray = Ray::new(player.pos, ray_dir);
+
+        // 1. Ceiling and floor
+        if self.per_pixel {
+            for (y, pixel) in column.iter_mut().enumerate() {
+                *pixel = shade_surface(self, &ray_dir, y);
+            }
+        } else {
+            column.copy_from_slice(&self.rows);
+        }
+
+        // Everything from `clip_bottom` downwards is already covered by a
+        // nearer wall. Walls are visited front to back, so we only ever need
+        // to draw the part that sticks out above what is already there.
+        let mut clip_bottom = height as i32;
+
+        while let Some(res) = ray.cast_raised(map) {
+            let (tx, ty) = (res.map_x as u32, res.map_y as u32);
+            let floor = map.get_floor_height(tx, ty).unwrap_or(0.0);
+            let top = if res.wall_value > 0 {
+                floor + map.get_wall_height(tx, ty).unwrap_or(1.0)
+            } else {
+                floor
+            };
+
+            // 2. Project the bottom (z = 0) and the top of the column relative to the eyes.
+            // A full-height wall at distance 1 is `focal` pixels tall.
+            let scale = focal / res.distance;
+            let bottom_y = (horizon + player.z * scale) as i32;
+            let top_y = (horizon - (top - player.z) * scale) as i32;
+
+            // Sunken walls (negative heights) have their top below their
+            // bottom and draw nothing
+            let start_y = clamp(top_y, 0, clip_bottom);
+            let end_y = clamp(bottom_y, 0, clip_bottom).max(start_y);
+
+            // 3. Draw the pixels into the column
+            let base = match (res.wall_value > 0, res.hit_vertical) {
+                (true, false) => WALL,
+                (true, true) => WALL_SIDE,
+                (false, false) => RISER, // Platform risers
+                (false, true) => RISER_SIDE,
+            };
+            let hit = player.pos.add(&ray_dir.scale(res.distance));
+            let light = options.lighting.then(|| {
+                // Sample the light just in front of the wall, in the open cell the ray came from
+                let point = hit.add(&ray_dir.normalize().scale(-0.01));
+                light::sample(map, &options.lights, &point)
+            });
+
+            let pixels = &mut column[start_y as usize..end_y as usize];
+            let is_wall = res.wall_value > 0;
+            let indexed_texture = match self.output {
+                Output::Indexed(mode) if is_wall => {
+                    mode.texture(res.wall_value).map(|texture| (mode, texture))
+                }
+                _ => None,
+            };
+            let texture = match self.output {
+                Output::Packed(format) if is_wall => options
+                    .wall_texture(res.wall_value)
+                    .map(|texture| (format, texture)),
+                _ => None,
+            };
+
+            // Where along the face the ray hit, and how far below the top each row is
+            let u = if res.hit_vertical { hit.y } else { hit.x };
+            let wall_top = horizon - (top - player.z) * scale;
+            let side = if res.hit_vertical { SIDE_LIGHT } else { 1.0 };
+
+            if let Some((mode, texture)) = indexed_texture {
+                let brightness = self.brightness(light, res.distance) * side;
+                let colormap = mode.colormaps.map(mode.colormaps.level(brightness));
+
+                let tx = ((u.fract() * texture.width() as f32) as u32).min(texture.width() - 1);
+                for (y, pixel) in (start_y..end_y).zip(pixels) {
+                    let v = (y as f32 + 0.5 - wall_top) / scale;
+                    let ty = (v.rem_euclid(1.0) * texture.height() as f32) as u32;
+                    let index = texture.get_index(tx, ty.min(texture.height() - 1));
+                    *pixel = colormap[index as usize] as u32;
+                }
+            } else if let Some((format, texture)) = texture {
+                // A full-height wall covers `scale` pixels, so distant walls
+                // squeeze many texels into each pixel
+                let lod = texture.mip_for(texture.height() as f32 / scale);
+                // Column-major textures hand over the whole strip when
+                // neither filtering nor a smaller mip level is needed
+                let strip = (texture.filter == Filter::Nearest && lod < 0.5)
+                    .then(|| {
+                        let tx = (u.fract() * texture.width() as f32) as u32;
+                        texture.column(tx.min(texture.width().saturating_sub(1)))
+                    })
+                    .flatten()
+                    .filter(|strip| !strip.is_empty());
+                for (y, pixel) in (start_y..end_y).zip(pixels) {
+                    let v = (y as f32 + 0.5 - wall_top) / scale;
+                    let color = match strip {
+                        Some(strip) => {
+                            let ty = (v.rem_euclid(1.0) * texture.height() as f32) as usize;
+                            let i = ty.min(texture.height() as usize - 1) * 4;
+                            Color::new(strip[i], strip[i + 1], strip[i + 2], strip[i + 3])
+                        }
+                        None => texture.sample(u.fract(), v.rem_euclid(1.0), lod),
+                    }
+                    .darkened(side);
+                    let color = light.map_or(color, |light| color.multiply(&light));
+                    *pixel = format.pack(options.fog.shade(color, res.distance));
+                }
+            } else {
+                pixels.fill(self.shade(base, light, res.distance));
+            }
+
+            clip_bottom = clip_bottom.min(start_y);
+
+            // Tops below eye level rise towards the horizon with distance,
+            // so only a column we look up at can hide everything behind it.
+            if clip_bottom <= 0 || (top >= self.max_top && top >= player.z) {
+                break;
+            }
+        }
+    }
+}
+
+/// Returns the flat color and distance of the floor or ceiling seen on screen row `y`.
+///
+/// Each row of the floor and ceiling lies at a fixed distance from the eyes.
+fn surface_at(player: &Player, y: usize, horizon: f32, focal: f32) -> (Color, f32) {
+    let row = y as f32 + 0.5 - horizon;
+    if row < 0.0 {
+        (CEILING, (1.0 - player.z).abs() * focal / -row)
+    } else {
+        (FLOOR, player.z * focal / row)
+    }
+}
+
+/// Returns the output pixel of the floor, ceiling or sky on row `y` of a column.
+fn shade_surface(frame: &Frame, ray_dir: &Vector2, y: usize) -> u32 {
+    let Frame {
+        player,
+        map,
+        options,
+        height,
+        horizon,
+        focal,
+        ..
+    } = *frame;
+    let (base, dist) = surface_at(player, y, horizon, focal);
+    let point = player.pos.add(&ray_dir.scale(dist));
+
+    let is_ceiling = (y as f32 + 0.5) < horizon;
+    if let Some(sky) = &options.sky
+        && is_ceiling
+        && point.x >= 0.0
+        && point.y >= 0.0
+        && map.is_open_sky(point.x as u32, point.y as u32)
+    {
+        let color = sky.sample(ray_dir.x, ray_dir.y, y, horizon, height);
+        return match frame.output {
+            Output::Packed(format) => format.pack(color),
+            Output::Indexed(mode) => mode.palette.lookup(color) as u32,
+        };
+    }
+
+    let texture = if is_ceiling {
+        options.ceiling_texture.as_ref()
+    } else {
+        options.floor_texture.as_ref()
+    };
+    let base = match texture {
+        Some(texture) => {
+            // Neighbouring rows are further apart the flatter the view
+            // angle, so the footprint of a pixel grows with distance squared
+            let eye = if is_ceiling {
+                (1.0 - player.z).abs()
+            } else {
+                player.z
+            };
+            let footprint = dist / focal * (dist / eye.max(f32::EPSILON)).max(1.0);
+            let lod = texture.mip_for(footprint * texture.width() as f32);
+            texture.sample(point.x.rem_euclid(1.0), point.y.rem_euclid(1.0), lod)
+        }
+        None => base,
+    };
+
+    let light = options
+        .lighting
+        .then(|| light::sample(map, &options.lights, &point));
+    frame.shade(base, light, dist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        framebuffer::{Framebuffer, Rect},
+        palette::Palette,
+        texture::{IndexedTexture, TextureLayout, Wrap},
+    };
+
+    #[test]
+    fn test_render_output() {
+        let mut map = Map::new(10, 10);
+        // Place a wall at X=5, Y=0 to 10 (a vertical strip)
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0)); // Standing at X=1, looking East
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+
+        render(&player, &map, &mut fb.view());
+
+        let middle_pixel = fb.get(10, 10).unwrap();
+
+        assert!(
+            middle_pixel == 0xFFCC0000 || middle_pixel == 0xFF880000,
+            "Expected wall color (Red), but found: {:#X}",
+            middle_pixel
+        );
+    }
+
+    #[test]
+    fn test_tall_wall_visible_behind_low_wall() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(3, y, 1);
+            map.set_wall_height(3, y, 0.25);
+            map.set_tile(6, y, 1);
+            map.set_wall_height(6, y, 3.0);
+        }
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+
+        render(&player, &map, &mut fb.view());
+
+        // The low wall fills the bottom of the center column...
+        assert_eq!(fb.get(10, 12).unwrap(), 0xFF880000);
+        // ...and the tall wall behind it towers over the horizon.
+        assert_eq!(fb.get(10, 5).unwrap(), 0xFF880000);
+    }
+
+    #[test]
+    fn test_sunken_walls_draw_nothing() {
+        let mut map = Map::new(8, 8);
+        for i in 0..8 {
+            for (x, y) in [(i, 0), (i, 7), (0, i), (7, i)] {
+                map.set_tile(x, y, 1);
+                map.set_wall_height(x, y, -0.5);
+            }
+        }
+        let player = Player::new(4.0, 4.0);
+        let mut fb = Framebuffer::new(64, 48, PixelFormat::Argb32);
+        render_with(&player, &map, &RenderOptions::default(), &mut fb.view());
+        assert_eq!(fb.get(32, 24).unwrap(), 0xFF111111);
+
+        // A normal wall behind a sunken one is still drawn
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(3, y, 1);
+            map.set_wall_height(3, y, -0.5);
+            map.set_tile(6, y, 1);
+        }
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        render(&player, &map, &mut fb.view());
+        assert_eq!(fb.get(10, 10).unwrap(), 0xFF880000);
+    }
+
+    #[test]
+    fn test_walls_keep_their_shape_when_widened() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+
+        // Same horizontal FOV, twice the columns: the wall must grow to match
+        let wall_height = |width| {
+            let mut fb = Framebuffer::new(width, 20, PixelFormat::Argb32);
+            render(&player, &map, &mut fb.view());
+            (0..20)
+                .filter(|&y| fb.get(width / 2, y) == Some(0xFF880000))
+                .count()
+        };
+        let (narrow, wide) = (wall_height(20), wall_height(40));
+        assert!(narrow > 0);
+        assert!(wide.abs_diff(2 * narrow) <= 1, "{narrow} vs {wide}");
+    }
+
+    #[test]
+    fn test_pitch_and_height_shift_the_view() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+
+        // Looking down moves the horizon (and the wall with it) to the top of
+        // the screen, so the middle of the screen shows floor
+        player.look(-0.5);
+        render(&player, &map, &mut fb.view());
+        assert_eq!(fb.get(10, 0).unwrap(), 0xFF880000);
+        assert_eq!(fb.get(10, 10).unwrap(), 0xFF111111);
+
+        // Standing on tiptoes far above the wall, we see over it
+        player.pitch = 0.0;
+        player.z = 2.0;
+        render(&player, &map, &mut fb.view());
+        assert_eq!(fb.get(10, 9).unwrap(), 0xFF333333);
+        assert_eq!(fb.get(10, 15).unwrap(), 0xFF880000);
+    }
+
+    #[test]
+    fn test_fog_darkens_distant_walls() {
+        let mut map = Map::new(20, 10);
+        for y in 0..10 {
+            map.set_tile(3, y, 1);
+            map.set_tile(15, y, 1);
+        }
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+        let options = RenderOptions {
+            fog: Fog {
+                diminish: 0.05,
+                ..Fog::default()
+            },
+            ..RenderOptions::default()
+        };
+
+        let mut near = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        render_with(&player, &map, &options, &mut near.view());
+
+        // Remove the near wall so the same column now sees the far one
+        let mut far = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        for y in 0..10 {
+            map.set_tile(3, y, 0);
+        }
+        render_with(&player, &map, &options, &mut far.view());
+
+        let red = |pixel: u32| (pixel >> 16) & 0xFF;
+        assert!(red(far.get(10, 10).unwrap()) < red(near.get(10, 10).unwrap()));
+    }
+
+    #[test]
+    fn test_lighting_modulates_walls_and_floor() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+        light::bake(&mut map, Color::new(0, 0, 0, 255), &[]);
+
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+        let mut options = RenderOptions {
+            lighting: true,
+            ..RenderOptions::default()
+        };
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+
+        // Pitch black without any light
+        render_with(&player, &map, &options, &mut fb.view());
+        assert_eq!(fb.get(10, 10).unwrap(), 0xFF000000);
+        assert_eq!(fb.get(10, 19).unwrap(), 0xFF000000);
+
+        // A torch right in front of the wall lights it up
+        options.lights.push(PointLight::new(4.5, 5.0, 3.0, 1.0));
+        render_with(&player, &map, &options, &mut fb.view());
+        assert_ne!(fb.get(10, 10).unwrap(), 0xFF000000);
+    }
+
+    #[test]
+    fn test_sky_only_above_open_tiles() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(9, y, 1);
+        }
+        let mut player = Player::new(1.5, 5.5);
+        player.rotate(math::deg_to_rad(90.0));
+
+        let options = RenderOptions {
+            sky: Some(Sky::Gradient {
+                zenith: Color::new(0, 0, 255, 255),
+                horizon: Color::new(0, 0, 255, 255),
+            }),
+            ..RenderOptions::default()
+        };
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+
+        render_with(&player, &map, &options, &mut fb.view());
+        assert_eq!(fb.get(10, 0).unwrap(), 0xFF0000FF);
+
+        // Roof over the whole map: back to the plain ceiling
+        map.covered.fill(true);
+        render_with(&player, &map, &options, &mut fb.view());
+        assert_eq!(fb.get_color(10, 0).unwrap().to_array(), CEILING.to_array());
+    }
+
+    #[test]
+    fn test_render_stays_inside_viewport() {
+        let map = Map::new(10, 10);
+        let player = Player::new(5.0, 5.0);
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Rgba8);
+
+        render(&player, &map, &mut fb.viewport(Rect::new(5, 5, 10, 10)));
+
+        assert_eq!(fb.get(4, 4), Some(0));
+        assert_eq!(fb.get(15, 15), Some(0));
+        assert_eq!(fb.get_color(5, 5).unwrap().to_array(), CEILING.to_array());
+        assert_eq!(fb.get_color(14, 14).unwrap().to_array(), FLOOR.to_array());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let map: Map = "##########\n#..1.....#\n#......2.#\n#.3......#\n##########"
+            .parse()
+            .unwrap();
+        let mut player = Player::new(1.5, 2.5);
+        player.set_angle(math::deg_to_rad(-20.0));
+        player.look(0.1);
+
+        let options = RenderOptions {
+            fog: Fog {
+                diminish: 0.08,
+                ..Fog::default()
+            },
+            lighting: true,
+            lights: vec![PointLight::new(4.0, 2.0, 5.0, 1.0)],
+            sky: Some(Sky::default()),
+            ..RenderOptions::default()
+        };
+
+        // An odd width so the last strip is narrower than the others
+        let mut serial = Framebuffer::new(77, 40, PixelFormat::Rgba8);
+        let mut parallel = Framebuffer::new(77, 40, PixelFormat::Rgba8);
+        render_with(&player, &map, &options, &mut serial.view());
+        render_parallel(&player, &map, &options, &mut parallel.view());
+
+        assert_eq!(serial.pixels(), parallel.pixels());
+
+        // A reused renderer resizes its buffer to each frame
+        let mut renderer = Renderer::new();
+        for (width, height) in [(33, 61), (77, 40)] {
+            let mut serial = Framebuffer::new(width, height, PixelFormat::Rgba8);
+            let mut parallel = Framebuffer::new(width, height, PixelFormat::Rgba8);
+            render_with(&player, &map, &options, &mut serial.view());
+            renderer.render_parallel(&player, &map, &options, &mut parallel.view());
+            assert_eq!(serial.pixels(), parallel.pixels());
+        }
+    }
+
+    #[test]
+    fn test_layouts_match() {
+        let map: Map = "#######\n#..1..#\n#.....#\n#######".parse().unwrap();
+        let mut player = Player::new(1.5, 2.5);
+        player.set_angle(math::deg_to_rad(-30.0));
+
+        let mut options = RenderOptions::default();
+        let mut column_major = Framebuffer::new(37, 23, PixelFormat::Bgra8);
+        render_with(&player, &map, &options, &mut column_major.view());
+
+        options.layout = RenderLayout::RowMajor;
+        let mut row_major = Framebuffer::new(37, 23, PixelFormat::Bgra8);
+        render_with(&player, &map, &options, &mut row_major.view());
+
+        assert_eq!(column_major.pixels(), row_major.pixels());
+    }
+
+    #[test]
+    fn test_renderer_reuses_its_buffer_across_sizes() {
+        let map: Map = "#######\n#..1..#\n#.....#\n#######".parse().unwrap();
+        let mut player = Player::new(1.5, 2.5);
+        player.set_angle(math::deg_to_rad(-30.0));
+        let options = RenderOptions::default();
+        let mut renderer = Renderer::new();
+
+        for (width, height) in [(37, 23), (16, 40), (37, 23)] {
+            let mut expected = Framebuffer::new(width, height, PixelFormat::Bgra8);
+            render_with(&player, &map, &options, &mut expected.view());
+
+            let mut fb = Framebuffer::new(width, height, PixelFormat::Bgra8);
+            renderer.render(&player, &map, &options, &mut fb.view());
+            assert_eq!(fb.pixels(), expected.pixels());
+        }
+    }
+
+    #[test]
+    fn test_indexed_matches_true_color_with_exact_palette() {
+        let map: Map = "#######\n#..1..#\n#.....#\n#######".parse().unwrap();
+        let mut player = Player::new(1.5, 2.5);
+        player.set_angle(math::deg_to_rad(-30.0));
+
+        let mut true_color = Framebuffer::new(37, 23, PixelFormat::Argb32);
+        render(&player, &map, &mut true_color.view());
+
+        let mode = IndexedMode::new(Palette::from_colors(&SURFACES));
+        let mut frame = IndexedFrame::new(37, 23);
+        render_indexed(&player, &map, &RenderOptions::default(), &mode, &mut frame);
+        let mut indexed = Framebuffer::new(37, 23, PixelFormat::Argb32);
+        frame.present(&mode.palette, &mut indexed.view());
+
+        assert_eq!(true_color.pixels(), indexed.pixels());
+    }
+
+    #[test]
+    fn test_indexed_walls_are_textured_and_shaded() {
+        let mut map = Map::new(20, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+
+        let mut colors = vec![Color::BLACK];
+        colors.extend((1..=255).map(|i| Color::new(i as u8, 0, 0, 255)));
+        let mut mode = IndexedMode::new(Palette::from_colors(&colors));
+        mode.textures.push(IndexedTexture::new(1, 1, vec![200]));
+        let mut frame = IndexedFrame::new(20, 20);
+
+        render_indexed(&player, &map, &RenderOptions::default(), &mode, &mut frame);
+        // The ray crosses an x grid line, so this is a darker side face
+        let lit = mode.colormaps.shade(200, SIDE_LIGHT);
+        assert!(lit > 0 && lit < 200);
+        assert_eq!(frame.get(10, 10), Some(lit));
+
+        // Diminishing picks a darker colormap
+        let options = RenderOptions {
+            fog: Fog {
+                diminish: 0.1,
+                ..Fog::default()
+            },
+            ..RenderOptions::default()
+        };
+        render_indexed(&player, &map, &options, &mode, &mut frame);
+        assert!(frame.get(10, 10).unwrap() < lit);
+    }
+
+    #[test]
+    fn test_textured_walls_and_floors() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+
+        let solid = |rgba: [u8; 4]| Texture::new(2, 2, rgba.repeat(4));
+        let options = RenderOptions {
+            wall_textures: vec![solid([0, 0, 255, 255])],
+            floor_texture: Some(solid([0, 255, 0, 255])),
+            ..RenderOptions::default()
+        };
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        render_with(&player, &map, &options, &mut fb.view());
+
+        // The ray crosses an x grid line, so this is a darker side face
+        let blue = Color::from_hex(0x0000FF).darkened(SIDE_LIGHT);
+        assert_eq!(fb.get_color(10, 10).unwrap(), blue);
+        assert_eq!(fb.get_color(10, 19).unwrap(), Color::from_hex(0x00FF00));
+        assert_eq!(fb.get_color(10, 0).unwrap(), CEILING);
+    }
+
+    #[test]
+    fn test_clamped_wall_textures_stay_within_each_face() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+        // Aim a quarter of the way along the face at y = 5
+        let mut player = Player::new(1.0, 5.25);
+        player.rotate(math::deg_to_rad(90.0));
+
+        // Red on the left half, blue on the right
+        let mut texture = Texture::new(2, 1, [255, 0, 0, 255, 0, 0, 255, 255].to_vec());
+        texture.wrap = Wrap::Clamp;
+        let options = RenderOptions {
+            wall_textures: vec![texture],
+            ..RenderOptions::default()
+        };
+        let mut fb = Framebuffer::new(20, 20, PixelFormat::Argb32);
+        render_with(&player, &map, &options, &mut fb.view());
+
+        let red = Color::from_hex(0xFF0000).darkened(SIDE_LIGHT);
+        assert_eq!(fb.get_color(10, 10).unwrap(), red);
+    }
+
+    #[test]
+    fn test_column_major_walls_match_row_major() {
+        let mut map = Map::new(10, 10);
+        for y in 0..10 {
+            map.set_tile(5, y, 1);
+        }
+        let mut player = Player::new(1.0, 5.3);
+        player.rotate(math::deg_to_rad(80.0));
+
+        let pixels = (0..8 * 8).flat_map(|i| [i as u8 * 4, 255 - i as u8, 0, 255]);
+        let row_major = Texture::new(8, 8, pixels.collect());
+        let column_major = row_major.clone().with_layout(TextureLayout::ColumnMajor);
+
+        let mut options = RenderOptions {
+            wall_textures: vec![row_major],
+            ..RenderOptions::default()
+        };
+        let mut expected = Framebuffer::new(32, 24, PixelFormat::Argb32);
+        render_with(&player, &map, &options, &mut expected.view());
+
+        options.wall_textures = vec![column_major];
+        let mut fb = Framebuffer::new(32, 24, PixelFormat::Argb32);
+        render_with(&player, &map, &options, &mut fb.view());
+        assert_eq!(fb.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn test_mipmaps_average_distant_walls() {
+        let mut map = Map::new(20, 10);
+        for y in 0..10 {
+            map.set_tile(12, y, 1);
+        }
+        let mut player = Player::new(1.0, 5.0);
+        player.rotate(math::deg_to_rad(90.0));
+
+        // One-texel black and white checkerboard
+        let pixels = (0..64 * 64)
+            .flat_map(|i| {
+                let c = if (i % 64 + i / 64) % 2 == 0 { 0 } else { 255 };
+                [c, c, c, 255]
+            })
+            .collect();
+        let mut options = RenderOptions {
+            wall_textures: vec![Texture::new(64, 64, pixels)],
+            ..RenderOptions::default()
+        };
+        let mut fb = Framebuffer::new(40, 40, PixelFormat::Argb32);
+
+        render_with(&player, &map, &options, &mut fb.view());
+        let aliased = fb.get_color(20, 19).unwrap().r;
+        assert!(aliased == 0 || aliased == 191, "{aliased}");
+
+        options.wall_textures[0].generate_mips();
+        render_with(&player, &map, &options, &mut fb.view());
+        let filtered = fb.get_color(20, 19).unwrap().r;
+        assert!((80..110).contains(&filtered), "{filtered}");
+    }
+}
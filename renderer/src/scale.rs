@@ -0,0 +1,245 @@
+use crate::{
+    color::Color,
+    framebuffer::{FrameView, Framebuffer, PixelFormat, Rect},
+};
+
+/// How a low-resolution frame is fitted into the window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Fill the whole window, ignoring the aspect ratio.
+    Stretch,
+    /// The largest size that keeps the aspect ratio, letterboxed.
+    Fit,
+    /// The largest whole multiple of the internal resolution, letterboxed.
+    /// Every source pixel becomes the same number of screen pixels.
+    ///
+    /// Both axes scale by whole numbers, so with a `pixel_aspect` other than
+    /// 1.0 the horizontal factor is rounded and the aspect ratio is only
+    /// approximately corrected.
+    Integer,
+}
+
+/// Nearest-neighbor upscaler from an internal resolution to the window.
+///
+/// All fields can be changed between frames.
+#[derive(Clone, Copy, Debug)]
+pub struct Scaler {
+    pub mode: ScaleMode,
+
+    /// The displayed width of one internal pixel divided by its height.
+    ///
+    /// 1.0 for square pixels. 320x200 shown on a 4:3 monitor, as DOS games
+    /// were, has pixels that are taller than wide: `(4.0 / 3.0) / (320.0 / 200.0)`.
+    pub pixel_aspect: f32,
+
+    /// The color of the letterbox bars.
+    pub border: Color,
+}
+
+impl Default for Scaler {
+    fn default() -> Self {
+        Self {
+            mode: ScaleMode::Fit,
+            pixel_aspect: 1.0,
+            border: Color::new(0, 0, 0, 255),
+        }
+    }
+}
+
+impl Scaler {
+    /// The pixel aspect that makes a 320x200 frame look 4:3.
+    pub const DOS_ASPECT: f32 = (4.0 / 3.0) / (320.0 / 200.0);
+
+    pub fn new(mode: ScaleMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Returns where a `src_w` x `src_h` frame lands in a `dst_w` x `dst_h` window.
+    pub fn dest_rect(&self, src_w: usize, src_h: usize, dst_w: usize, dst_h: usize) -> Rect {
+        if src_w == 0 || src_h == 0 {
+            return Rect::new(0, 0, 0, 0);
+        }
+
+        // The source size once its pixels are made square
+        let aspect_w = src_w as f32 * self.pixel_aspect.max(f32::EPSILON);
+        let fit = (dst_w as f32 / aspect_w).min(dst_h as f32 / src_h as f32);
+
+        let (w, h) = match self.mode {
+            ScaleMode::Stretch => return Rect::new(0, 0, dst_w, dst_h),
+            ScaleMode::Fit => (
+                (aspect_w * fit).round() as usize,
+                (src_h as f32 * fit).round() as usize,
+            ),
+            ScaleMode::Integer => {
+                if fit >= 1.0 {
+                    let n = fit.floor();
+                    // The whole horizontal factor closest to the corrected
+                    // one that still fits the window
+                    let mut nx = (n * self.pixel_aspect).round().max(1.0) as usize;
+                    if nx > 1 && nx * src_w > dst_w {
+                        nx -= 1;
+                    }
+                    (nx * src_w, n as usize * src_h)
+                } else {
+                    // Fall back to shrinking if the window is smaller than the frame
+                    (
+                        (aspect_w * fit).round() as usize,
+                        (src_h as f32 * fit).round() as usize,
+                    )
+                }
+            }
+        };
+
+        let (w, h) = (w.min(dst_w), h.min(dst_h));
+        Rect::new((dst_w - w) / 2, (dst_h - h) / 2, w, h)
+    }
+
+    /// Scales `src` into `dst`, filling the bars around it with `border`.
+    pub fn present(&self, src: &Framebuffer, dst: &mut FrameView) {
+        let format = dst.format();
+        let width = dst.width();
+        self.scale_rows(src, width, dst.height(), format, |y, row| {
+            dst.row_mut(y).copy_from_slice(row)
+        });
+    }
+
+    /// Scales `src` into an RGBA8 byte frame (such as `Pixels::frame_mut`) of
+    /// `width` x `height` pixels.
+    ///
+    /// # Panics
+    /// Panics if `frame` is not exactly `width * height * 4` bytes long.
+    pub fn present_rgba8(&self, src: &Framebuffer, frame: &mut [u8], width: usize, height: usize) {
+        assert_eq!(frame.len(), width * height * 4, "frame size mismatch");
+        self.scale_rows(src, width, height, PixelFormat::Rgba8, |y, row| {
+            let out = &mut frame[y * width * 4..(y + 1) * width * 4];
+            for (bytes, pixel) in out.chunks_exact_mut(4).zip(row) {
+                bytes.copy_from_slice(&pixel.to_ne_bytes());
+            }
+        });
+    }
+
+    /// Produces every output row, packed as `format`, and hands it to `write_row`.
+    fn scale_rows(
+        &self,
+        src: &Framebuffer,
+        width: usize,
+        height: usize,
+        format: PixelFormat,
+        mut write_row: impl FnMut(usize, &[u32]),
+    ) {
+        let rect = self.dest_rect(src.width(), src.height(), width, height);
+        let border = format.pack(self.border);
+
+        // Which source column feeds each output column
+        let src_x: Vec<usize> = (0..rect.width)
+            .map(|dx| dx * src.width() / rect.width)
+            .collect();
+
+        let mut row = vec![border; width];
+        let mut last_sy = None;
+
+        for y in 0..height {
+            let inside = y >= rect.y && y < rect.y + rect.height;
+            if !inside {
+                if last_sy.is_some() {
+                    row.fill(border);
+                    last_sy = None;
+                }
+                write_row(y, &row);
+                continue;
+            }
+
+            // Consecutive output rows often sample the same source row
+            let sy = (y - rect.y) * src.height() / rect.height;
+            if last_sy != Some(sy) {
+                let line = &src.pixels()[sy * src.stride()..];
+                for (out, &sx) in row[rect.x..rect.x + rect.width].iter_mut().zip(&src_x) {
+                    *out = src.format().convert(line[sx], format);
+                }
+                last_sy = Some(sy);
+            }
+            write_row(y, &row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dest_rect_modes() {
+        let fit = Scaler::new(ScaleMode::Fit);
+        assert_eq!(
+            fit.dest_rect(320, 200, 1920, 1080),
+            Rect::new(96, 0, 1728, 1080)
+        );
+
+        let integer = Scaler::new(ScaleMode::Integer);
+        assert_eq!(
+            integer.dest_rect(320, 200, 1920, 1080),
+            Rect::new(160, 40, 1600, 1000)
+        );
+
+        let stretch = Scaler::new(ScaleMode::Stretch);
+        assert_eq!(
+            stretch.dest_rect(320, 200, 1920, 1080),
+            Rect::new(0, 0, 1920, 1080)
+        );
+    }
+
+    #[test]
+    fn test_dos_aspect_is_four_by_three() {
+        let scaler = Scaler {
+            pixel_aspect: Scaler::DOS_ASPECT,
+            ..Scaler::default()
+        };
+        let rect = scaler.dest_rect(320, 200, 1920, 1080);
+        assert_eq!((rect.width, rect.height), (1440, 1080));
+
+        // 5x vertically and the closest whole factor to 5 * 5/6 horizontally
+        let integer = Scaler {
+            mode: ScaleMode::Integer,
+            ..scaler
+        };
+        let rect = integer.dest_rect(320, 200, 1920, 1080);
+        assert_eq!((rect.width, rect.height), (1280, 1000));
+    }
+
+    #[test]
+    fn test_present_doubles_pixels_with_letterbox() {
+        let mut src = Framebuffer::new(2, 1, PixelFormat::Argb32);
+        src.view().put(0, 0, Color::new(255, 0, 0, 255));
+        src.view().put(1, 0, Color::new(0, 0, 255, 255));
+
+        let scaler = Scaler {
+            mode: ScaleMode::Integer,
+            border: Color::new(1, 1, 1, 255),
+            ..Scaler::default()
+        };
+        let mut dst = Framebuffer::new(5, 4, PixelFormat::Argb32);
+        scaler.present(&src, &mut dst.view());
+
+        // 2x1 scaled by 2 is 4x2, centered in 5x4
+        assert_eq!(dst.get(0, 0), Some(0xFF010101));
+        assert_eq!(dst.get(0, 1), Some(0xFFFF0000));
+        assert_eq!(dst.get(1, 2), Some(0xFFFF0000));
+        assert_eq!(dst.get(2, 1), Some(0xFF0000FF));
+        assert_eq!(dst.get(3, 2), Some(0xFF0000FF));
+        assert_eq!(dst.get(4, 1), Some(0xFF010101));
+        assert_eq!(dst.get(0, 3), Some(0xFF010101));
+    }
+
+    #[test]
+    fn test_present_rgba8() {
+        let mut src = Framebuffer::new(1, 1, PixelFormat::Argb32);
+        src.clear(Color::new(10, 20, 30, 255));
+
+        let mut frame = [0u8; 2 * 2 * 4];
+        Scaler::new(ScaleMode::Stretch).present_rgba8(&src, &mut frame, 2, 2);
+        assert_eq!(frame[12..16], [10, 20, 30, 255]);
+    }
+}
@@ -0,0 +1,116 @@
+use std::f32::consts::PI;
+
+use crate::{color::Color, palette::palette, texture::Texture};
+
+/// What is drawn above the walls where the ceiling is open.
+#[derive(Clone, Debug)]
+pub enum Sky {
+    /// A 360° panorama. The full texture width wraps once around the player,
+    /// and its bottom row sits on the horizon.
+    Texture(Texture),
+    /// A vertical gradient from `zenith` (top of the screen) to `horizon`.
+    Gradient { zenith: Color, horizon: Color },
+}
+
+impl Default for Sky {
+    /// A gradient built from `palette::SKY`, brightening towards the horizon.
+    fn default() -> Self {
+        Sky::Gradient {
+            zenith: palette::SKY,
+            horizon: palette::SKY.lerp(&Color::new(255, 255, 255, 255), 0.35),
+        }
+    }
+}
+
+impl Sky {
+    /// Returns the sky color seen along the ray direction on screen row `y`.
+    ///
+    /// `horizon` is the screen row of the horizon, so the sky scrolls
+    /// vertically with the player's pitch.
+    pub fn sample(
+        &self,
+        ray_dir_x: f32,
+        ray_dir_y: f32,
+        y: usize,
+        horizon: f32,
+        height: usize,
+    ) -> Color {
+        // 0.0 at the top of an unpitched screen, 1.0 on the horizon
+        let v = (y as f32 - (horizon - height as f32 / 2.0)) / (height as f32 / 2.0);
+        let v = math::clamp(v, 0.0, 1.0);
+
+        match self {
+            Sky::Gradient { zenith, horizon } => zenith.lerp(horizon, v),
+            Sky::Texture(texture) => {
+                if texture.width() == 0 || texture.height() == 0 {
+                    return palette::SKY;
+                }
+
+                let angle = math::wrap_angle(ray_dir_y.atan2(ray_dir_x));
+                let u = angle / (2.0 * PI);
+
+                let tx = ((u * texture.width() as f32) as u32).min(texture.width() - 1);
+                let ty = ((v * texture.height() as f32) as u32).min(texture.height() - 1);
+                texture.get(tx, ty).unwrap_or(palette::SKY)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_follows_horizon() {
+        let sky = Sky::Gradient {
+            zenith: Color::new(0, 0, 0, 255),
+            horizon: Color::new(200, 200, 200, 255),
+        };
+        assert_eq!(
+            sky.sample(1.0, 0.0, 0, 50.0, 100).to_array(),
+            [0, 0, 0, 255]
+        );
+        assert_eq!(sky.sample(1.0, 0.0, 25, 50.0, 100).r, 100);
+
+        // Looking up by 25 pixels pushes the same row closer to the zenith
+        assert_eq!(
+            sky.sample(1.0, 0.0, 25, 75.0, 100).to_array(),
+            [0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn test_texture_wraps_with_view_angle() {
+        // 4x1 panorama: one color per quarter turn, starting East and turning
+        // towards +Y
+        let texture = Texture::new(
+            4,
+            1,
+            vec![
+                255, 0, 0, 255, // 0-90°
+                0, 255, 0, 255, // 90-180°
+                0, 0, 255, 255, // 180-270°
+                255, 255, 255, 255, // 270-360°
+            ],
+        );
+        let sky = Sky::Texture(texture);
+
+        assert_eq!(
+            sky.sample(1.0, 1.0, 0, 50.0, 100).to_array(),
+            [255, 0, 0, 255]
+        );
+        assert_eq!(
+            sky.sample(-1.0, 1.0, 0, 50.0, 100).to_array(),
+            [0, 255, 0, 255]
+        );
+        assert_eq!(
+            sky.sample(-1.0, -1.0, 0, 50.0, 100).to_array(),
+            [0, 0, 255, 255]
+        );
+        assert_eq!(
+            sky.sample(1.0, -1.0, 0, 50.0, 100).to_array(),
+            [255, 255, 255, 255]
+        );
+    }
+}
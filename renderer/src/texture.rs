@@ -1,19 +1,464 @@
+use std::{fmt, path::Path};
+
+use crate::{color::Color, framebuffer::PixelFormat, palette::Palette};
+
+/// What happens to texture coordinates outside `0.0..1.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    /// Tile the texture, for walls and floors.
+    #[default]
+    Repeat,
+    /// Stretch the edge texels, for sprites and skies.
+    Clamp,
+}
+
+/// How a sample between texel centers is colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// The texel the sample falls in: crisp, blocky pixels.
+    #[default]
+    Nearest,
+    /// A blend of the four closest texels: smooth up close.
+    Bilinear,
+}
+
+/// The order texels are stored in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureLayout {
+    /// One row after another, like image files.
+    #[default]
+    RowMajor,
+    /// One column after another, so the vertical strips walls are drawn
+    /// from are contiguous in memory. The renderer reads whole strips from
+    /// these when drawing full-size walls with nearest filtering.
+    ColumnMajor,
+}
+
+impl TextureLayout {
+    /// Returns the byte offset of texel `(x, y)` in a `width` x `height` image.
+    fn offset(self, width: u32, height: u32, x: u32, y: u32) -> usize {
+        let texel = match self {
+            TextureLayout::RowMajor => y as usize * width as usize + x as usize,
+            TextureLayout::ColumnMajor => x as usize * height as usize + y as usize,
+        };
+        texel * 4
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureError {
+    /// The pixel data does not hold exactly `width * height` texels.
+    SizeMismatch { expected: usize, actual: usize },
+    /// The texture's size does not fit in memory.
+    TooLarge { width: u32, height: u32 },
+    /// An indexed texture with no texels to sample.
+    Empty,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::SizeMismatch { expected, actual } => {
+                write!(f, "expected {expected} bytes of pixel data, got {actual}")
+            }
+            TextureError::TooLarge { width, height } => {
+                write!(f, "a {width}x{height} texture is too large")
+            }
+            TextureError::Empty => write!(f, "indexed textures need at least one texel"),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// One level of a mip chain, half the size of the one before.
+#[derive(Clone, Debug)]
+struct MipLevel {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// An RGBA8 image with a guaranteed `width * height * 4` bytes of pixels.
+#[derive(Clone, Debug)]
 pub struct Texture {
-    pub width: u32,
-    pub height: u32,
-    pub pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    layout: TextureLayout,
+    /// Addressing used by `sample`.
+    pub wrap: Wrap,
+    /// Filtering used by `sample`.
+    pub filter: Filter,
+    /// Levels 1 and up, filled in by `generate_mips`. Level 0 is `pixels`.
+    mips: Vec<MipLevel>,
 }
 
 impl Texture {
+    /// Creates a texture from RGBA8 pixels, row by row, with repeat
+    /// addressing, nearest filtering and no mipmaps.
+    ///
+    /// Fails unless `pixels` holds exactly `width * height * 4` bytes.
+    pub fn try_new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, TextureError> {
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|texels| texels.checked_mul(4))
+            .ok_or(TextureError::TooLarge { width, height })?;
+        if pixels.len() != expected {
+            return Err(TextureError::SizeMismatch {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            layout: TextureLayout::RowMajor,
+            wrap: Wrap::default(),
+            filter: Filter::default(),
+            mips: Vec::new(),
+        })
+    }
+
+    /// Like `try_new`, for pixel data that is known to be the right size.
+    ///
+    /// # Panics
+    ///
+    /// If `pixels` does not hold exactly `width * height * 4` bytes.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        match Self::try_new(width, height, pixels) {
+            Ok(texture) => texture,
+            Err(e) => panic!("invalid texture: {e}"),
+        }
+    }
+
+    /// Loads an image file (PNG, BMP, ...) as an RGBA8 texture.
+    pub fn load(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self::new(image.width(), image.height(), image.into_raw()))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn layout(&self) -> TextureLayout {
+        self.layout
+    }
+
+    /// The raw RGBA8 bytes, in the order given by `layout`.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the texture stored in `layout`, mip chain included.
+    pub fn with_layout(mut self, layout: TextureLayout) -> Self {
+        if layout != self.layout {
+            let from = self.layout;
+            self.pixels = relayout(&self.pixels, self.width, self.height, from, layout);
+            for mip in &mut self.mips {
+                mip.pixels = relayout(&mip.pixels, mip.width, mip.height, from, layout);
+            }
+            self.layout = layout;
+        }
+        self
+    }
+
+    /// Returns the texel at `(x, y)`, or `None` outside the texture.
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        if x < self.width && y < self.height {
+            let i = self.layout.offset(self.width, self.height, x, y);
+            let p = &self.pixels[i..i + 4];
+            Some(Color::new(p[0], p[1], p[2], p[3]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the RGBA bytes of the texel at `(x, y)`.
+    ///
+    /// # Panics
+    ///
+    /// If `(x, y)` is outside the texture.
+    #[deprecated(note = "use `get`, which returns `None` outside the texture")]
     pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
-        let starting_index: usize = ((x + (y * self.width)) * 4).try_into().unwrap();
+        let color = self.get(x, y).expect("texel out of bounds");
+        [color.r, color.g, color.b, color.a]
+    }
+
+    /// Returns the texel at `(x, y)` without checking the coordinates.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be less than `width()` and `y` less than `height()`.
+    pub unsafe fn get_unchecked(&self, x: u32, y: u32) -> Color {
+        let i = self.layout.offset(self.width, self.height, x, y);
+        // SAFETY: the caller keeps (x, y) inside the texture, and `try_new`
+        // guarantees 4 bytes for every texel inside it
+        unsafe {
+            Color::new(
+                *self.pixels.get_unchecked(i),
+                *self.pixels.get_unchecked(i + 1),
+                *self.pixels.get_unchecked(i + 2),
+                *self.pixels.get_unchecked(i + 3),
+            )
+        }
+    }
+
+    /// Returns the texel at `(x, y)` packed as `format`, ready to write to a framebuffer.
+    pub fn get_packed(&self, x: u32, y: u32, format: PixelFormat) -> Option<u32> {
+        self.get(x, y).map(|color| format.pack(color))
+    }
+
+    /// Returns column `x` as contiguous RGBA8 bytes, top to bottom.
+    ///
+    /// Only column-major textures store columns contiguously; this is
+    /// `None` for row-major ones and for `x` outside the texture.
+    pub fn column(&self, x: u32) -> Option<&[u8]> {
+        if self.layout != TextureLayout::ColumnMajor || x >= self.width {
+            return None;
+        }
+        let len = self.height as usize * 4;
+        let start = x as usize * len;
+        Some(&self.pixels[start..start + len])
+    }
+
+    /// Returns row `y` as contiguous RGBA8 bytes, left to right.
+    ///
+    /// `None` for column-major textures and for `y` outside the texture.
+    pub fn row(&self, y: u32) -> Option<&[u8]> {
+        if self.layout != TextureLayout::RowMajor || y >= self.height {
+            return None;
+        }
+        let len = self.width as usize * 4;
+        let start = y as usize * len;
+        Some(&self.pixels[start..start + len])
+    }
+
+    /// Builds the mip chain: each level averages 2x2 blocks of the one
+    /// before, down to 1x1. Empty textures have no mips.
+    pub fn generate_mips(&mut self) {
+        self.mips.clear();
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let (mut width, mut height) = (self.width, self.height);
+        while width > 1 || height > 1 {
+            let source = self.mips.last().map_or(&self.pixels, |mip| &mip.pixels);
+            let next = downsample(source, width, height, self.layout);
+            width = next.width;
+            height = next.height;
+            self.mips.push(next);
+        }
+    }
+
+    /// Number of mip levels, including the full-size texture.
+    pub fn mip_levels(&self) -> usize {
+        1 + self.mips.len()
+    }
+
+    /// Returns the mip level for a surface where one screen pixel covers
+    /// `texels_per_pixel` texels of the full-size texture.
+    pub fn mip_for(&self, texels_per_pixel: f32) -> f32 {
+        if self.mips.is_empty() || texels_per_pixel <= 1.0 {
+            0.0
+        } else {
+            texels_per_pixel.log2()
+        }
+    }
+
+    /// Samples the texture at normalized `(u, v)`, where `(1.0, 1.0)` is the
+    /// bottom-right corner, using the texture's `wrap` and `filter` modes.
+    ///
+    /// `lod` picks the mip level (0.0 is full size, rounded to the nearest
+    /// level and clamped to the chain). Empty textures sample as transparent.
+    pub fn sample(&self, u: f32, v: f32, lod: f32) -> Color {
+        if self.width == 0 || self.height == 0 {
+            return Color::TRANSPARENT;
+        }
+        let level = (lod.max(0.0).round() as usize).min(self.mips.len());
+        let (width, height, pixels) = match level {
+            0 => (self.width, self.height, &self.pixels),
+            n => {
+                let mip = &self.mips[n - 1];
+                (mip.width, mip.height, &mip.pixels)
+            }
+        };
+
+        let texel = |x: i64, y: i64| {
+            let (x, y) = match self.wrap {
+                Wrap::Repeat => (x.rem_euclid(width as i64), y.rem_euclid(height as i64)),
+                Wrap::Clamp => (x.clamp(0, width as i64 - 1), y.clamp(0, height as i64 - 1)),
+            };
+            let i = self.layout.offset(width, height, x as u32, y as u32);
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]].map(|c| c as f32)
+        };
+
+        let (x, y) = (u * width as f32, v * height as f32);
+        let rgba = match self.filter {
+            Filter::Nearest => texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // Weights are measured from texel centers
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = lerp4(texel(x0, y0), texel(x0 + 1, y0), fx);
+                let bottom = lerp4(texel(x0, y0 + 1), texel(x0 + 1, y0 + 1), fx);
+                lerp4(top, bottom, fy)
+            }
+        };
+        let [r, g, b, a] = rgba.map(|c| c.round() as u8);
+        Color::new(r, g, b, a)
+    }
+
+    /// Like `sample`, packed as `format`.
+    pub fn sample_packed(&self, u: f32, v: f32, lod: f32, format: PixelFormat) -> u32 {
+        format.pack(self.sample(u, v, lod))
+    }
+}
+
+fn lerp4(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Copies a `width` x `height` RGBA8 image from one layout to another.
+fn relayout(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    from: TextureLayout,
+    to: TextureLayout,
+) -> Vec<u8> {
+    let mut out = vec![0; pixels.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let src = from.offset(width, height, x, y);
+            let dst = to.offset(width, height, x, y);
+            out[dst..dst + 4].copy_from_slice(&pixels[src..src + 4]);
+        }
+    }
+    out
+}
 
-        [
-            self.pixels[starting_index],
-            self.pixels[starting_index + 1],
-            self.pixels[starting_index + 2],
-            self.pixels[starting_index + 3],
-        ]
+/// Halves an RGBA8 image, averaging each 2x2 block. Odd edges reuse the
+/// last row or column. The result keeps `layout`.
+fn downsample(pixels: &[u8], width: u32, height: u32, layout: TextureLayout) -> MipLevel {
+    let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+    let mut out = vec![0; (w * h * 4) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            let xs = [(2 * x).min(width - 1), (2 * x + 1).min(width - 1)];
+            let ys = [(2 * y).min(height - 1), (2 * y + 1).min(height - 1)];
+            let dst = layout.offset(w, h, x, y);
+            for c in 0..4 {
+                let mut sum = 0u32;
+                for sy in ys {
+                    for sx in xs {
+                        sum += pixels[layout.offset(width, height, sx, sy) + c] as u32;
+                    }
+                }
+                out[dst + c] = ((sum + 2) / 4) as u8;
+            }
+        }
+    }
+    MipLevel {
+        width: w,
+        height: h,
+        pixels: out,
+    }
+}
+
+/// A texture stored as palette indices, for the indexed rendering mode.
+///
+/// Always holds at least one texel, so the renderer can sample it without
+/// checking.
+#[derive(Clone, Debug)]
+pub struct IndexedTexture {
+    width: u32,
+    height: u32,
+    indices: Vec<u8>,
+}
+
+impl IndexedTexture {
+    /// Creates a texture from palette indices, row by row.
+    ///
+    /// Fails unless both sides are non-zero and `indices` holds exactly
+    /// `width * height` entries.
+    pub fn try_new(width: u32, height: u32, indices: Vec<u8>) -> Result<Self, TextureError> {
+        if width == 0 || height == 0 {
+            return Err(TextureError::Empty);
+        }
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .ok_or(TextureError::TooLarge { width, height })?;
+        if indices.len() != expected {
+            return Err(TextureError::SizeMismatch {
+                expected,
+                actual: indices.len(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            indices,
+        })
+    }
+
+    /// Like `try_new`, for index data that is known to be valid.
+    ///
+    /// # Panics
+    ///
+    /// If a side is zero or `indices` does not hold `width * height` entries.
+    pub fn new(width: u32, height: u32, indices: Vec<u8>) -> Self {
+        match Self::try_new(width, height, indices) {
+            Ok(texture) => texture,
+            Err(e) => panic!("invalid indexed texture: {e}"),
+        }
+    }
+
+    /// Converts an RGBA texture by mapping every pixel to its nearest palette
+    /// entry. Exact, unlike `Palette::lookup`, which is meant for per-frame
+    /// work rather than one-time conversions like this.
+    ///
+    /// Fails if `texture` is empty.
+    pub fn from_texture(texture: &Texture, palette: &Palette) -> Result<Self, TextureError> {
+        let (width, height) = (texture.width(), texture.height());
+        let indices = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| texture.get(x, y).map_or(0, |color| palette.nearest(color)))
+            .collect();
+        Self::try_new(width, height, indices)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The raw indices, row by row.
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+
+    /// Returns the index at `(x, y)`.
+    ///
+    /// # Panics
+    ///
+    /// If `(x, y)` is outside the texture.
+    pub fn get_index(&self, x: u32, y: u32) -> u8 {
+        assert!(x < self.width && y < self.height, "texel out of bounds");
+        self.indices[y as usize * self.width as usize + x as usize]
     }
 }
 
@@ -22,21 +467,18 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_get_pixel() {
         // Create a tiny 2x2 texture (4 pixels total)
         // Pixel colors: [Red, Green, Blue, White]
         let pixels = vec![
-            255, 0, 0, 255,   // (0,0) - Red
-            0, 255, 0, 255,   // (1,0) - Green
-            0, 0, 255, 255,   // (0,1) - Blue
+            255, 0, 0, 255, // (0,0) - Red
+            0, 255, 0, 255, // (1,0) - Green
+            0, 0, 255, 255, // (0,1) - Blue
             255, 255, 255, 255, // (1,1) - White
         ];
 
-        let texture = Texture {
-            width: 2,
-            height: 2,
-            pixels,
-        };
+        let texture = Texture::new(2, 2, pixels);
 
         // Check the Red pixel
         assert_eq!(texture.get_pixel(0, 0), [255, 0, 0, 255]);
@@ -47,4 +489,178 @@ mod tests {
         // Check the White pixel
         assert_eq!(texture.get_pixel(1, 1), [255, 255, 255, 255]);
     }
+
+    #[test]
+    fn test_get() {
+        // Create a tiny 2x2 texture (4 pixels total)
+        // Pixel colors: [Red, Green, Blue, White]
+        let pixels = vec![
+            255, 0, 0, 255, // (0,0) - Red
+            0, 255, 0, 255, // (1,0) - Green
+            0, 0, 255, 255, // (0,1) - Blue
+            255, 255, 255, 255, // (1,1) - White
+        ];
+
+        let texture = Texture::new(2, 2, pixels);
+
+        // Check the Red pixel
+        assert_eq!(texture.get(0, 0), Some(Color::new(255, 0, 0, 255)));
+        // Check the Green pixel
+        assert_eq!(texture.get(1, 0), Some(Color::new(0, 255, 0, 255)));
+        // Check the Blue pixel
+        assert_eq!(texture.get(0, 1), Some(Color::new(0, 0, 255, 255)));
+        // Check the White pixel
+        assert_eq!(texture.get(1, 1), Some(Color::WHITE));
+        // Outside the texture
+        assert_eq!(texture.get(2, 0), None);
+        assert_eq!(texture.get(0, 2), None);
+
+        assert_eq!(
+            texture.get_packed(1, 0, PixelFormat::Argb32),
+            Some(0xFF00FF00)
+        );
+        // SAFETY: (1, 1) is inside the 2x2 texture
+        assert_eq!(unsafe { texture.get_unchecked(1, 1) }, Color::WHITE);
+    }
+
+    #[test]
+    fn test_try_new_checks_the_size() {
+        assert_eq!(
+            Texture::try_new(2, 2, vec![0; 15]).unwrap_err(),
+            TextureError::SizeMismatch {
+                expected: 16,
+                actual: 15
+            }
+        );
+        assert!(Texture::try_new(0, 0, Vec::new()).is_ok());
+        assert!(matches!(
+            Texture::try_new(u32::MAX, u32::MAX, Vec::new()),
+            Err(TextureError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_column_major_layout() {
+        let pixels = (0..3 * 2).flat_map(|i| [i as u8, 0, 0, 255]).collect();
+        let row_major = Texture::new(3, 2, pixels);
+        let mut column_major = row_major.clone().with_layout(TextureLayout::ColumnMajor);
+
+        for y in 0..2 {
+            for x in 0..3 {
+                assert_eq!(column_major.get(x, y), row_major.get(x, y));
+            }
+        }
+        // Column 1 holds texels (1, 0) and (1, 1), which are 1 and 4
+        assert_eq!(
+            column_major.column(1),
+            Some(&[1, 0, 0, 255, 4, 0, 0, 255][..])
+        );
+        assert_eq!(row_major.column(1), None);
+        assert_eq!(row_major.row(1).unwrap().len(), 12);
+
+        column_major.generate_mips();
+        let mut expected = row_major.clone();
+        expected.generate_mips();
+        assert_eq!(
+            column_major.sample(0.5, 0.5, 1.0),
+            expected.sample(0.5, 0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_indexed_from_texture() {
+        let texture = Texture::new(2, 1, vec![250, 10, 0, 255, 0, 0, 0, 255]);
+        let palette = Palette::from_colors(&[Color::BLACK, Color::new(255, 0, 0, 255)]);
+
+        let indexed = IndexedTexture::from_texture(&texture, &palette).unwrap();
+        assert_eq!(indexed.get_index(0, 0), 1);
+        assert_eq!(indexed.get_index(1, 0), 0);
+
+        // Close shades that share a lookup table bucket stay apart
+        let ramp = Palette::from_colors(&[Color::new(96, 0, 0, 255), Color::new(100, 0, 0, 255)]);
+        let texture = Texture::new(1, 1, vec![97, 0, 0, 255]);
+        let indexed = IndexedTexture::from_texture(&texture, &ramp).unwrap();
+        assert_eq!(indexed.get_index(0, 0), 0);
+
+        let empty = Texture::new(0, 3, Vec::new());
+        assert_eq!(
+            IndexedTexture::from_texture(&empty, &palette).unwrap_err(),
+            TextureError::Empty
+        );
+    }
+
+    #[test]
+    fn test_indexed_try_new_checks_the_size() {
+        assert_eq!(
+            IndexedTexture::try_new(2, 2, vec![0; 3]).unwrap_err(),
+            TextureError::SizeMismatch {
+                expected: 4,
+                actual: 3
+            }
+        );
+        assert_eq!(
+            IndexedTexture::try_new(0, 4, Vec::new()).unwrap_err(),
+            TextureError::Empty
+        );
+        assert_eq!(IndexedTexture::new(1, 2, vec![3, 4]).get_index(0, 1), 4);
+    }
+
+    fn checker() -> Texture {
+        // Black and white 2x2 checkerboard
+        let pixels = [0u8, 255, 255, 0]
+            .iter()
+            .flat_map(|&c| [c, c, c, 255])
+            .collect();
+        Texture::new(2, 2, pixels)
+    }
+
+    #[test]
+    fn test_sample_wraps_or_clamps() {
+        let mut texture = checker();
+        assert_eq!(texture.sample(0.25, 0.25, 0.0), Color::BLACK);
+        assert_eq!(texture.sample(1.25, -0.75, 0.0), Color::BLACK);
+        assert_eq!(texture.sample(1.75, 0.25, 0.0), Color::WHITE);
+
+        texture.wrap = Wrap::Clamp;
+        assert_eq!(texture.sample(1.75, 0.25, 0.0), Color::WHITE);
+        assert_eq!(texture.sample(5.0, 5.0, 0.0), Color::BLACK);
+    }
+
+    #[test]
+    fn test_bilinear_blends_neighbours() {
+        let mut texture = checker();
+        texture.filter = Filter::Bilinear;
+        // Exactly on a texel center
+        assert_eq!(texture.sample(0.25, 0.25, 0.0), Color::BLACK);
+        // Halfway between a black and a white texel
+        assert_eq!(texture.sample(0.5, 0.25, 0.0).r, 128);
+    }
+
+    #[test]
+    fn test_mip_chain_averages_down_to_one_texel() {
+        let mut texture = checker();
+        assert_eq!(texture.mip_levels(), 1);
+        assert_eq!(texture.mip_for(4.0), 0.0);
+
+        texture.generate_mips();
+        assert_eq!(texture.mip_levels(), 2);
+        assert_eq!(texture.mip_for(4.0), 2.0);
+        assert_eq!(
+            texture.sample(0.25, 0.25, 1.0),
+            Color::new(128, 128, 128, 255)
+        );
+        // Beyond the end of the chain
+        assert_eq!(
+            texture.sample(0.25, 0.25, 9.0),
+            Color::new(128, 128, 128, 255)
+        );
+
+        let mut wide = Texture::new(5, 2, vec![255; 5 * 2 * 4]);
+        wide.generate_mips();
+        assert_eq!(wide.mip_levels(), 3); // 5x2, 2x1, 1x1
+
+        let mut empty = Texture::new(0, 5, Vec::new());
+        empty.generate_mips();
+        assert_eq!(empty.mip_levels(), 1);
+    }
 }
@@ -0,0 +1,171 @@
+//! Golden-image regression tests for the renderer.
+//!
+//! Every scene is rendered at a fixed resolution and compared against a
+//! checked-in PNG in `tests/golden/`. A channel may differ by at most
+//! `TOLERANCE` before a pixel counts as wrong.
+//!
+//! On failure, the actual frame and a diff image (mismatching pixels in
+//! magenta) are written to the test temp directory. After an intentional
+//! change to the renderer, regenerate the references with:
+//!
+//! ```text
+//! BLESS_GOLDEN=1 cargo test -p renderer --test golden
+//! ```
+
+use std::{env, path::PathBuf};
+
+use image::{Rgba, RgbaImage};
+use renderer::{RenderOptions, headless::render_to_image, map::Map, player::Player};
+
+/// The maximum difference allowed per color channel.
+const TOLERANCE: u8 = 2;
+
+const RESOLUTIONS: [(u32, u32); 2] = [(64, 48), (160, 100)];
+
+const ROOM: &str = "\
+##########
+#........#
+#..1.....#
+#........#
+#....2...#
+#........#
+##########";
+
+const CORRIDOR: &str = "\
+############
+#..........#
+############";
+
+struct Scene {
+    name: &'static str,
+    map: &'static str,
+    pos: (f32, f32),
+    /// Degrees, 0 = +X, 90 = +Y.
+    angle: f32,
+}
+
+const SCENES: [Scene; 5] = [
+    Scene {
+        name: "corridor",
+        map: CORRIDOR,
+        pos: (1.5, 1.5),
+        angle: 0.0,
+    },
+    Scene {
+        name: "corner",
+        map: ROOM,
+        pos: (5.0, 3.0),
+        angle: -45.0,
+    },
+    Scene {
+        name: "diagonal",
+        map: ROOM,
+        pos: (1.5, 5.5),
+        angle: -30.0,
+    },
+    Scene {
+        name: "point_blank",
+        map: ROOM,
+        pos: (1.05, 3.5),
+        angle: 180.0,
+    },
+    Scene {
+        name: "map_edge",
+        map: "#...\n....\n.#..",
+        pos: (1.5, 1.5),
+        angle: -110.0,
+    },
+];
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn render_scene(scene: &Scene, width: u32, height: u32) -> RgbaImage {
+    let map: Map = scene.map.parse().expect("scene map is valid");
+    let mut player = Player::new(scene.pos.0, scene.pos.1);
+    player.set_angle(math::deg_to_rad(scene.angle));
+    render_to_image(&player, &map, &RenderOptions::default(), width, height)
+}
+
+/// Compares two images and returns the number of mismatching pixels and a diff image.
+fn compare(expected: &RgbaImage, actual: &RgbaImage) -> (usize, RgbaImage) {
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut mismatches = 0;
+
+    for (x, y, a) in actual.enumerate_pixels() {
+        let e = expected.get_pixel(x, y);
+        let bad =
+            a.0.iter()
+                .zip(e.0.iter())
+                .any(|(a, e)| a.abs_diff(*e) > TOLERANCE);
+
+        if bad {
+            mismatches += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 255, 255]));
+        } else {
+            // Dim the matching pixels so the mismatches stand out
+            let [r, g, b, _] = a.0;
+            diff.put_pixel(x, y, Rgba([r / 4, g / 4, b / 4, 255]));
+        }
+    }
+
+    (mismatches, diff)
+}
+
+#[test]
+fn golden_images() {
+    let bless = env::var_os("BLESS_GOLDEN").is_some();
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden-diff");
+    let mut failures = Vec::new();
+
+    for scene in &SCENES {
+        for (width, height) in RESOLUTIONS {
+            let name = format!("{}_{}x{}", scene.name, width, height);
+            let reference = golden_dir().join(format!("{name}.png"));
+            let actual = render_scene(scene, width, height);
+
+            if bless {
+                std::fs::create_dir_all(golden_dir()).unwrap();
+                actual.save(&reference).unwrap();
+                continue;
+            }
+
+            let expected = match image::open(&reference) {
+                Ok(image) => image.to_rgba8(),
+                Err(e) => {
+                    failures.push(format!("{name}: cannot load {}: {e}", reference.display()));
+                    continue;
+                }
+            };
+
+            if expected.dimensions() != actual.dimensions() {
+                failures.push(format!(
+                    "{name}: size {:?} does not match reference {:?}",
+                    actual.dimensions(),
+                    expected.dimensions()
+                ));
+                continue;
+            }
+
+            let (mismatches, diff) = compare(&expected, &actual);
+            if mismatches > 0 {
+                std::fs::create_dir_all(&out_dir).unwrap();
+                actual
+                    .save(out_dir.join(format!("{name}.actual.png")))
+                    .unwrap();
+                diff.save(out_dir.join(format!("{name}.diff.png"))).unwrap();
+                failures.push(format!(
+                    "{name}: {mismatches} pixels differ (see {})",
+                    out_dir.display()
+                ));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden images do not match (rerun with BLESS_GOLDEN=1 to accept):\n{}",
+        failures.join("\n")
+    );
+}